@@ -3,9 +3,9 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use prism_protocol::gov::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollResponse, PollsResponse,
-    PrismWithdrawOrdersResponse, QueryMsg, VotersResponse, VotersResponseItem,
-    VotingTokensResponse,
+    Cw20HookMsg, DelegatedPowerResponse, DelegatorsResponse, ExecuteMsg, InstantiateMsg,
+    PollResponse, PollsResponse, PrismWithdrawOrdersResponse, QueryMsg, QueuedPollsResponse,
+    VoteDelegationResponse, VotersResponse, VotersResponseItem, VotingTokensResponse,
 };
 
 fn main() {
@@ -20,8 +20,12 @@ fn main() {
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(PollResponse), &out_dir);
     export_schema(&schema_for!(PollsResponse), &out_dir);
+    export_schema(&schema_for!(QueuedPollsResponse), &out_dir);
     export_schema(&schema_for!(VotingTokensResponse), &out_dir);
     export_schema(&schema_for!(VotersResponse), &out_dir);
     export_schema(&schema_for!(VotersResponseItem), &out_dir);
     export_schema(&schema_for!(PrismWithdrawOrdersResponse), &out_dir);
+    export_schema(&schema_for!(VoteDelegationResponse), &out_dir);
+    export_schema(&schema_for!(DelegatedPowerResponse), &out_dir);
+    export_schema(&schema_for!(DelegatorsResponse), &out_dir);
 }
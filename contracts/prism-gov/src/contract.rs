@@ -2,12 +2,22 @@
 use cosmwasm_std::entry_point;
 use prism_protocol::internal::parse_reply_instantiate_data;
 
-use crate::polls::{cast_vote, create_poll, end_poll, execute_poll, failed_poll, snapshot_poll};
+use crate::delegation::{
+    delegate_votes, query_delegated_power, query_delegators, query_vote_delegation,
+    undelegate_votes,
+};
+use crate::polls::{
+    cancel_poll, cast_vote, create_poll, end_poll, execute_poll, execute_poll_msgs, failed_poll,
+    poll_execute_msgs, query_queued_polls, snapshot_poll, veto_poll,
+};
 use crate::state::{
     config_read, config_store, poll_read, poll_voter_read, read_poll_voters, read_polls,
-    read_tmp_poll_id, store_last_poll_id, Config,
+    read_tmp_poll_id, store_last_poll_id, Config, Poll,
+};
+use crate::voting::{
+    lock_voting_tokens, query_lock_positions, query_voting_power, query_voting_tokens,
+    stake_voting_tokens, withdraw_voting_tokens,
 };
-use crate::voting::{query_voting_tokens, stake_voting_tokens, withdraw_voting_tokens};
 
 use cosmwasm_std::{
     from_binary, to_binary, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Reply,
@@ -18,12 +28,12 @@ use cw20::{Cw20ReceiveMsg, MinterResponse};
 use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 use crate::xprism::{
-    claim_redeemed_prism, mint_xprism, query_prism_withdraw_orders, query_xprism_state,
-    redeem_xprism, TOTAL_PENDING_WITHDRAW,
+    claim_redeemed_prism, instant_redeem_xprism, mint_xprism, query_prism_withdraw_orders,
+    query_xprism_state, redeem_xprism, TOTAL_PENDING_WITHDRAW,
 };
 use prism_protocol::common::OrderBy;
 use prism_protocol::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollChoiceResponse, PollResponse,
     PollStatus, PollsResponse, QueryMsg, VoterInfo, VotersResponse, VotersResponseItem,
 };
 
@@ -46,6 +56,9 @@ pub fn instantiate(
     validate_quorum(msg.quorum)?;
     validate_threshold(msg.threshold)?;
     validate_poll_gas_limit(msg.poll_gas_limit)?;
+    if let Some(instant_redemption_fee) = msg.instant_redemption_fee {
+        validate_instant_redemption_fee(instant_redemption_fee)?;
+    }
 
     let config = Config {
         prism_token: deps.api.addr_canonicalize(&msg.prism_token)?,
@@ -59,6 +72,11 @@ pub fn instantiate(
         snapshot_period: msg.snapshot_period,
         redemption_time: msg.redemption_time,
         poll_gas_limit: msg.poll_gas_limit,
+        instant_redemption_fee: msg.instant_redemption_fee,
+        guardian: msg
+            .guardian
+            .map(|guardian| deps.api.addr_canonicalize(&guardian))
+            .transpose()?,
     };
 
     config_store(deps.storage).save(&config)?;
@@ -104,6 +122,8 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             snapshot_period,
             redemption_time,
             poll_gas_limit,
+            instant_redemption_fee,
+            guardian,
         } => update_config(
             deps,
             info,
@@ -116,8 +136,16 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             snapshot_period,
             redemption_time,
             poll_gas_limit,
+            instant_redemption_fee,
+            guardian,
         ),
-        ExecuteMsg::WithdrawVotingTokens { amount } => withdraw_voting_tokens(deps, info, amount),
+        ExecuteMsg::RemoveGuardian {} => remove_guardian(deps, info),
+        ExecuteMsg::WithdrawVotingTokens { amount } => {
+            withdraw_voting_tokens(deps, env, info, amount)
+        }
+        ExecuteMsg::LockVotingTokens { amount, duration } => {
+            lock_voting_tokens(deps, env, info, amount, duration)
+        }
         ExecuteMsg::CastVote {
             poll_id,
             vote,
@@ -125,8 +153,13 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         } => cast_vote(deps, env, info, poll_id, vote, amount),
         ExecuteMsg::EndPoll { poll_id } => end_poll(deps, env, poll_id),
         ExecuteMsg::ExecutePoll { poll_id } => execute_poll(deps, env, poll_id),
+        ExecuteMsg::ExecutePollMsgs { poll_id } => execute_poll_msgs(deps, env, info, poll_id),
+        ExecuteMsg::CancelPoll { poll_id } => cancel_poll(deps, info, poll_id),
+        ExecuteMsg::VetoPoll { poll_id } => veto_poll(deps, env, info, poll_id),
         ExecuteMsg::SnapshotPoll { poll_id } => snapshot_poll(deps, env, poll_id),
         ExecuteMsg::ClaimRedeemedXprism {} => claim_redeemed_prism(deps, env, info),
+        ExecuteMsg::DelegateVotes { delegate } => delegate_votes(deps, info, delegate),
+        ExecuteMsg::UndelegateVotes {} => undelegate_votes(deps, info),
     }
 }
 
@@ -148,7 +181,9 @@ pub fn receive_cw20(
                 title,
                 description,
                 link,
-                execute_msg,
+                execute_msgs,
+                poll_type,
+                choices,
             }) => create_poll(
                 deps,
                 env,
@@ -157,11 +192,16 @@ pub fn receive_cw20(
                 title,
                 description,
                 link,
-                execute_msg,
+                execute_msgs,
+                poll_type,
+                choices,
             ),
             Ok(Cw20HookMsg::RedeemXprism {}) => {
                 redeem_xprism(deps, env, cw20_msg.sender, cw20_msg.amount)
             }
+            Ok(Cw20HookMsg::InstantRedeemXprism {}) => {
+                instant_redeem_xprism(deps, env, cw20_msg.sender, cw20_msg.amount)
+            }
             _ => Err(StdError::generic_err("invalid cw20 hook message")),
         }
     } else if config.prism_token == sender_raw {
@@ -222,6 +262,8 @@ pub fn update_config(
     snapshot_period: Option<u64>,
     redemption_time: Option<u64>,
     poll_gas_limit: Option<u64>,
+    instant_redemption_fee: Option<Decimal>,
+    guardian: Option<String>,
 ) -> StdResult<Response> {
     let api = deps.api;
     config_store(deps.storage).update(|mut config| {
@@ -268,11 +310,33 @@ pub fn update_config(
             config.poll_gas_limit = poll_gas_limit;
         }
 
+        if let Some(instant_redemption_fee) = instant_redemption_fee {
+            validate_instant_redemption_fee(instant_redemption_fee)?;
+            config.instant_redemption_fee = Some(instant_redemption_fee);
+        }
+
+        if let Some(guardian) = guardian {
+            config.guardian = Some(api.addr_canonicalize(&guardian)?);
+        }
+
         Ok(config)
     })?;
     Ok(Response::default())
 }
 
+pub fn remove_guardian(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let api = deps.api;
+    config_store(deps.storage).update(|mut config| {
+        if config.owner != api.addr_canonicalize(info.sender.as_str())? {
+            return Err(StdError::generic_err("unauthorized"));
+        }
+
+        config.guardian = None;
+        Ok(config)
+    })?;
+    Ok(Response::new().add_attribute("action", "remove_guardian"))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -306,6 +370,29 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             order_by,
         )?),
         QueryMsg::XprismState {} => to_binary(&query_xprism_state(deps, env)?),
+        QueryMsg::QueuedPolls {
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_queued_polls(deps, start_after, limit, order_by)?),
+        QueryMsg::LockPositions { address } => {
+            to_binary(&query_lock_positions(deps, env, address)?)
+        }
+        QueryMsg::VotingPower { address } => to_binary(&query_voting_power(deps, env, address)?),
+        QueryMsg::VoteDelegation { address } => to_binary(&query_vote_delegation(deps, address)?),
+        QueryMsg::DelegatedPower { delegate } => to_binary(&query_delegated_power(deps, delegate)?),
+        QueryMsg::Delegators {
+            delegate,
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&query_delegators(
+            deps,
+            delegate,
+            start_after,
+            limit,
+            order_by,
+        )?),
     }
 }
 
@@ -326,6 +413,12 @@ fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         snapshot_period: config.snapshot_period,
         redemption_time: config.redemption_time,
         poll_gas_limit: config.poll_gas_limit,
+        instant_redemption_fee: config.instant_redemption_fee,
+        guardian: config
+            .guardian
+            .map(|guardian| deps.api.addr_humanize(&guardian))
+            .transpose()?
+            .map(|guardian| guardian.to_string()),
     })
 }
 
@@ -334,6 +427,8 @@ fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
         Some(poll) => poll,
         None => return Err(StdError::generic_err("Poll does not exist")),
     };
+    let execute_msgs = poll_execute_msgs(deps.api, &poll)?;
+    let choices = poll_choices_response(&poll);
 
     Ok(PollResponse {
         id: poll.id,
@@ -344,23 +439,30 @@ fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
         description: poll.description,
         link: poll.link,
         deposit_amount: poll.deposit_amount,
-        execute_data: if let Some(execute_data) = poll.execute_data {
-            Some(PollExecuteMsg {
-                contract: deps.api.addr_humanize(&execute_data.contract)?.to_string(),
-                msg: execute_data.msg,
-            })
-        } else {
-            None
-        },
+        execute_msgs,
         yes_votes: poll.yes_votes,
         no_votes: poll.no_votes,
         abstain_votes: poll.abstain_votes,
         supply_snapshot: poll.supply_snapshot,
         required_quorum: poll.required_quorum,
         required_threshold: poll.required_threshold,
+        poll_type: poll.poll_type,
+        choices,
+        winning_choice: poll.winning_choice,
     })
 }
 
+fn poll_choices_response(poll: &Poll) -> Vec<PollChoiceResponse> {
+    poll.choices
+        .iter()
+        .map(|choice| PollChoiceResponse {
+            description: choice.description.clone(),
+            execute_msgs: choice.execute_msgs.clone(),
+            votes: choice.votes,
+        })
+        .collect()
+}
+
 fn query_polls(
     deps: Deps,
     filter: Option<PollStatus>,
@@ -381,20 +483,16 @@ fn query_polls(
                 description: poll.description.to_string(),
                 link: poll.link.clone(),
                 deposit_amount: poll.deposit_amount,
-                execute_data: if let Some(execute_data) = poll.execute_data.clone() {
-                    Some(PollExecuteMsg {
-                        contract: deps.api.addr_humanize(&execute_data.contract)?.to_string(),
-                        msg: execute_data.msg,
-                    })
-                } else {
-                    None
-                },
+                execute_msgs: poll_execute_msgs(deps.api, poll)?,
                 yes_votes: poll.yes_votes,
                 no_votes: poll.no_votes,
                 abstain_votes: poll.abstain_votes,
                 supply_snapshot: poll.supply_snapshot,
                 required_quorum: poll.required_quorum,
                 required_threshold: poll.required_threshold,
+                poll_type: poll.poll_type.clone(),
+                choices: poll_choices_response(poll),
+                winning_choice: poll.winning_choice,
             })
         })
         .collect();
@@ -482,7 +580,23 @@ fn validate_poll_gas_limit(poll_gas_limit: u64) -> StdResult<()> {
     }
 }
 
+/// validate_instant_redemption_fee returns an error if the fee is invalid
+/// (we require 0-1, excluded)
+fn validate_instant_redemption_fee(instant_redemption_fee: Decimal) -> StdResult<()> {
+    if instant_redemption_fee >= Decimal::one() {
+        Err(StdError::generic_err(
+            "instant_redemption_fee must be smaller than 1",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
+    // withdraw orders keep their (address, end_time) keys, orders redeemed in the
+    // same block are merged from now on, so existing orders need no migration
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
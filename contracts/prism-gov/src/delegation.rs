@@ -0,0 +1,140 @@
+use crate::state::{
+    bank_read, delegator_store, read_delegators, vote_delegation_read, vote_delegation_store,
+    DEFAULT_LIMIT,
+};
+
+use cosmwasm_std::{
+    attr, CanonicalAddr, Deps, DepsMut, MessageInfo, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+use prism_protocol::common::OrderBy;
+use prism_protocol::gov::{
+    DelegatedPowerResponse, DelegatorsResponse, DelegatorsResponseItem, VoteDelegationResponse,
+};
+
+/// Delegators are iterated when their delegate votes, so their number is capped
+pub const MAX_DELEGATORS_PER_DELEGATE: usize = 100;
+
+/*
+ * Delegate the sender's voting power, replacing any previous delegation
+ */
+pub fn delegate_votes(deps: DepsMut, info: MessageInfo, delegate: String) -> StdResult<Response> {
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let delegate_raw = deps.api.addr_canonicalize(&delegate)?;
+    if sender_address_raw == delegate_raw {
+        return Err(StdError::generic_err("Can not delegate votes to yourself"));
+    }
+
+    if let Some(previous) =
+        vote_delegation_read(deps.storage).may_load(sender_address_raw.as_slice())?
+    {
+        if previous == delegate_raw {
+            return Err(StdError::generic_err("Votes are already delegated to it"));
+        }
+        delegator_store(deps.storage, &previous).remove(sender_address_raw.as_slice());
+    }
+
+    let delegators = read_delegators(deps.storage, &delegate_raw, None, None, None)?;
+    if delegators.len() >= MAX_DELEGATORS_PER_DELEGATE {
+        return Err(StdError::generic_err(format!(
+            "A delegate can not have more than {} delegators",
+            MAX_DELEGATORS_PER_DELEGATE
+        )));
+    }
+
+    vote_delegation_store(deps.storage).save(sender_address_raw.as_slice(), &delegate_raw)?;
+    delegator_store(deps.storage, &delegate_raw).save(sender_address_raw.as_slice(), &true)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "delegate_votes"),
+        attr("delegator", info.sender.as_str()),
+        attr("delegate", delegate),
+    ]))
+}
+
+/*
+ * Stop delegating. Votes already cast by the delegate stay counted
+ */
+pub fn undelegate_votes(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let delegate_raw = vote_delegation_read(deps.storage)
+        .may_load(sender_address_raw.as_slice())?
+        .ok_or_else(|| StdError::generic_err("Votes are not delegated"))?;
+
+    vote_delegation_store(deps.storage).remove(sender_address_raw.as_slice());
+    delegator_store(deps.storage, &delegate_raw).remove(sender_address_raw.as_slice());
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "undelegate_votes"),
+        attr("delegator", info.sender.as_str()),
+        attr("delegate", deps.api.addr_humanize(&delegate_raw)?.as_str()),
+    ]))
+}
+
+/// Staked balance of a delegator, counted for its delegate
+pub fn delegator_balance(storage: &dyn Storage, delegator: &CanonicalAddr) -> StdResult<Uint128> {
+    Ok(bank_read(storage)
+        .may_load(delegator.as_slice())?
+        .unwrap_or_default()
+        .deposit)
+}
+
+pub fn query_vote_delegation(deps: Deps, address: String) -> StdResult<VoteDelegationResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    let delegate = vote_delegation_read(deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .map(|delegate| deps.api.addr_humanize(&delegate))
+        .transpose()?
+        .map(|delegate| delegate.to_string());
+    Ok(VoteDelegationResponse { delegate })
+}
+
+pub fn query_delegated_power(deps: Deps, delegate: String) -> StdResult<DelegatedPowerResponse> {
+    let delegate_raw = deps.api.addr_canonicalize(&delegate)?;
+    let delegators = read_delegators(deps.storage, &delegate_raw, None, None, None)?;
+
+    let mut delegated_power = Uint128::zero();
+    for delegator in delegators.iter() {
+        delegated_power += delegator_balance(deps.storage, delegator)?;
+    }
+
+    Ok(DelegatedPowerResponse {
+        delegate,
+        delegated_power,
+        delegators: delegators.len() as u32,
+    })
+}
+
+pub fn query_delegators(
+    deps: Deps,
+    delegate: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<DelegatorsResponse> {
+    let delegate_raw = deps.api.addr_canonicalize(&delegate)?;
+    let start_after = start_after
+        .map(|start_after| deps.api.addr_canonicalize(&start_after))
+        .transpose()?;
+    let delegators = read_delegators(
+        deps.storage,
+        &delegate_raw,
+        start_after,
+        Some(limit.unwrap_or(DEFAULT_LIMIT)),
+        order_by,
+    )?;
+
+    let delegators: StdResult<Vec<DelegatorsResponseItem>> = delegators
+        .iter()
+        .map(|delegator| {
+            Ok(DelegatorsResponseItem {
+                delegator: deps.api.addr_humanize(delegator)?.to_string(),
+                balance: delegator_balance(deps.storage, delegator)?,
+            })
+        })
+        .collect();
+
+    Ok(DelegatorsResponse {
+        delegators: delegators?,
+    })
+}
@@ -1,4 +1,5 @@
 pub mod contract;
+mod delegation;
 mod polls;
 pub mod state;
 mod voting;
@@ -1,16 +1,22 @@
 use crate::contract::POLL_EXECUTE_REPLY_ID;
 use crate::state::{
-    bank_read, bank_store, config_read, config_store, poll_indexer_store, poll_store,
-    poll_voter_read, poll_voter_store, pop_last_poll_id, store_tmp_poll_id, Config, ExecuteData,
-    Poll,
+    bank_read, bank_store, config_read, config_store, poll_delegated_vote_read,
+    poll_delegated_vote_store, poll_indexer_store, poll_store, poll_voter_read, poll_voter_store,
+    pop_last_poll_id, read_delegators, read_polls, remove_poll_delegated_votes, store_tmp_poll_id,
+    Config, DelegatedVote, Poll, PollChoice,
 };
+use crate::voting::{amount_voting_power, voting_power};
 
 use cosmwasm_std::{
-    attr, to_binary, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, ReplyOn, Response, StdError,
-    StdResult, SubMsg, Uint128, WasmMsg,
+    attr, to_binary, Api, BankMsg, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
-use prism_protocol::gov::{PollExecuteMsg, PollStatus, VoteOption, VoterInfo};
+use prism_protocol::common::OrderBy;
+use prism_protocol::gov::{
+    ExecuteMsg, PollChoiceMsg, PollStatus, PollType, QueuedPollResponse, QueuedPollsResponse,
+    VoteOption, VoterInfo,
+};
 use prismswap::querier::query_supply;
 
 const MIN_TITLE_LENGTH: usize = 4;
@@ -19,6 +25,10 @@ const MIN_DESC_LENGTH: usize = 4;
 const MAX_DESC_LENGTH: usize = 256;
 const MIN_LINK_LENGTH: usize = 12;
 const MAX_LINK_LENGTH: usize = 128;
+const MIN_CHOICE_LENGTH: usize = 1;
+const MAX_CHOICE_LENGTH: usize = 64;
+const MIN_POLL_CHOICES: usize = 2;
+const MAX_POLL_CHOICES: usize = 10;
 
 pub const MAX_POLL_VOTES_PER_USER: usize = 20;
 
@@ -34,12 +44,20 @@ pub fn create_poll(
     title: String,
     description: String,
     link: Option<String>,
-    poll_execute_msg: Option<PollExecuteMsg>,
+    execute_msgs: Option<Vec<CosmosMsg>>,
+    poll_type: Option<PollType>,
+    choices: Option<Vec<PollChoiceMsg>>,
 ) -> StdResult<Response> {
     validate_title(&title)?;
     validate_description(&description)?;
     validate_link(&link)?;
 
+    let execute_msgs = execute_msgs.unwrap_or_default();
+    validate_execute_msgs(deps.api, &execute_msgs)?;
+
+    let poll_type = poll_type.unwrap_or_default();
+    let choices = validate_choices(deps.api, &poll_type, choices, &execute_msgs)?;
+
     let config: Config = config_store(deps.storage).load()?;
     if deposit_amount < config.proposal_deposit {
         return Err(StdError::generic_err(format!(
@@ -48,15 +66,6 @@ pub fn create_poll(
         )));
     }
 
-    let poll_execute_data = if let Some(poll_execute_msg) = poll_execute_msg {
-        Some(ExecuteData {
-            contract: deps.api.addr_canonicalize(&poll_execute_msg.contract)?,
-            msg: poll_execute_msg.msg,
-        })
-    } else {
-        None
-    };
-
     let sender_address_raw = deps.api.addr_canonicalize(&proposer)?;
     let current_seconds = env.block.time.seconds();
     let poll_id = pop_last_poll_id(deps.storage)?;
@@ -71,11 +80,18 @@ pub fn create_poll(
         title,
         description,
         link,
-        execute_data: poll_execute_data,
+        execute_data: None,
+        execute_msgs,
         deposit_amount,
         supply_snapshot: None,
         required_quorum: config.quorum,
         required_threshold: config.threshold,
+        poll_type,
+        choices,
+        choice_votes: Uint128::zero(),
+        winning_choice: None,
+        participation: Some(Uint128::zero()),
+        deposit_held: false,
     };
 
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &new_poll)?;
@@ -113,9 +129,10 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
 
     let no = a_poll.no_votes.u128();
     let yes = a_poll.yes_votes.u128();
-    let abstain = a_poll.abstain_votes.u128();
 
-    let tallied_weight = yes + no + abstain;
+    // quorum is reached on the staked amount that voted, locks only boost the
+    // voting power that decides the outcome
+    let tallied_weight = poll_participation(&a_poll).u128();
 
     let mut poll_status = PollStatus::Rejected;
     let mut rejected_reason = "";
@@ -155,7 +172,24 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
             }))
         }
     } else {
-        if yes != 0u128 && Decimal::from_ratio(yes, yes + no) > a_poll.required_threshold {
+        if a_poll.poll_type != PollType::YesNo {
+            // Threshold: the choice with the most votes needs more than the threshold
+            // of the voting power cast on choices (excluding “Abstain” votes).
+            match winning_choice(&a_poll) {
+                Some(choice)
+                    if Decimal::from_ratio(
+                        a_poll.choices[choice as usize].votes,
+                        a_poll.choice_votes,
+                    ) > a_poll.required_threshold =>
+                {
+                    a_poll.winning_choice = Some(choice);
+                    poll_status = PollStatus::Passed;
+                    passed = true;
+                }
+                Some(_) => rejected_reason = "Threshold not reached",
+                None => rejected_reason = "No winning choice",
+            }
+        } else if yes != 0u128 && Decimal::from_ratio(yes, yes + no) > a_poll.required_threshold {
             //Threshold: More than 50% of the tokens that participated in the vote
             // (after excluding “Abstain” votes) need to have voted in favor of the proposal (“Yes”).
             poll_status = PollStatus::Passed;
@@ -164,8 +198,15 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
             rejected_reason = "Threshold not reached";
         }
 
-        // Refunds deposit only when quorum is reached
-        if !a_poll.deposit_amount.is_zero() {
+        // Refunds deposit only when quorum is reached, the deposit of a passed poll
+        // the guardian can veto is kept until it is executed or vetoed
+        if passed
+            && config.guardian.is_some()
+            && !a_poll.deposit_amount.is_zero()
+            && !poll_execute_msgs(deps.api, &a_poll)?.is_empty()
+        {
+            a_poll.deposit_held = true;
+        } else if !a_poll.deposit_amount.is_zero() {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: xprism_addr.to_string(),
                 funds: vec![],
@@ -184,6 +225,7 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
     // Update poll status
     a_poll.status = poll_status;
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    remove_poll_delegated_votes(deps.storage, poll_id)?;
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "end_poll"),
@@ -193,6 +235,113 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
     ]))
 }
 
+/*
+ * Cancels a poll in progress. The creator can cancel it until a vote is cast,
+ * the owner at any time. The deposit is refunded if no votes were cast and
+ * burned otherwise, as for polls that do not reach quorum.
+ */
+pub fn cancel_poll(deps: DepsMut, info: MessageInfo, poll_id: u64) -> StdResult<Response> {
+    let config: Config = config_read(deps.storage).load()?;
+    let mut a_poll: Poll = poll_store(deps.storage)
+        .load(&poll_id.to_be_bytes())
+        .map_err(|_| StdError::generic_err("Poll does not exist"))?;
+
+    if a_poll.status != PollStatus::InProgress {
+        return Err(StdError::generic_err("Poll is not in progress"));
+    }
+
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let has_votes = !(a_poll.yes_votes + a_poll.no_votes + a_poll.abstain_votes).is_zero();
+    if sender_address_raw != config.owner && (sender_address_raw != a_poll.creator || has_votes) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if !a_poll.deposit_amount.is_zero() {
+        let xprism_addr = deps.api.addr_humanize(&config.xprism_token.unwrap())?;
+        let msg = if has_votes {
+            to_binary(&Cw20ExecuteMsg::Burn {
+                amount: a_poll.deposit_amount,
+            })?
+        } else {
+            to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deps.api.addr_humanize(&a_poll.creator)?.to_string(),
+                amount: a_poll.deposit_amount,
+            })?
+        };
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: xprism_addr.to_string(),
+            funds: vec![],
+            msg,
+        }));
+    }
+
+    poll_indexer_store(deps.storage, &PollStatus::InProgress).remove(&poll_id.to_be_bytes());
+    poll_indexer_store(deps.storage, &PollStatus::Cancelled).save(&poll_id.to_be_bytes(), &true)?;
+
+    // locked balances only count polls in progress, so the votes are unlocked
+    // and cleaned up the same way as for ended polls
+    a_poll.status = PollStatus::Cancelled;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+    remove_poll_delegated_votes(deps.storage, poll_id)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "cancel_poll"),
+        attr("poll_id", poll_id.to_string()),
+        attr("deposit_refunded", (!has_votes).to_string()),
+    ]))
+}
+
+/*
+ * Vetoes a passed poll during its effective delay, only the guardian can veto.
+ * A deposit held for the poll is burned.
+ */
+pub fn veto_poll(deps: DepsMut, env: Env, info: MessageInfo, poll_id: u64) -> StdResult<Response> {
+    let config: Config = config_read(deps.storage).load()?;
+    let mut a_poll: Poll = poll_store(deps.storage)
+        .load(&poll_id.to_be_bytes())
+        .map_err(|_| StdError::generic_err("Poll does not exist"))?;
+
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if config.guardian != Some(sender_address_raw) {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if a_poll.status != PollStatus::Passed {
+        return Err(StdError::generic_err("Poll is not in passed status"));
+    }
+
+    if a_poll.end_time + config.effective_delay <= env.block.time.seconds() {
+        return Err(StdError::generic_err("Effective delay has expired"));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if a_poll.deposit_held {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.xprism_token.unwrap())?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: a_poll.deposit_amount,
+            })?,
+        }));
+    }
+
+    poll_indexer_store(deps.storage, &PollStatus::Passed).remove(&poll_id.to_be_bytes());
+    poll_indexer_store(deps.storage, &PollStatus::Vetoed).save(&poll_id.to_be_bytes(), &true)?;
+
+    a_poll.status = PollStatus::Vetoed;
+    poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "veto_poll"),
+        attr("poll_id", poll_id.to_string()),
+        attr("deposit_burned", a_poll.deposit_held.to_string()),
+    ]))
+}
+
 /*
  * Execute a msg of passed poll.
  */
@@ -215,31 +364,111 @@ pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response
     a_poll.status = PollStatus::Executed;
     poll_store(deps.storage).save(&poll_id.to_be_bytes(), &a_poll)?;
 
-    let mut messages: Vec<SubMsg> = vec![];
-    if let Some(execute_data) = a_poll.execute_data {
-        messages.push(SubMsg {
+    if poll_execute_msgs(deps.api, &a_poll)?.is_empty() {
+        return Err(StdError::generic_err("The poll does not have execute_data"));
+    }
+    store_tmp_poll_id(deps.storage, a_poll.id)?;
+
+    // a held deposit is refunded once the poll can no longer be vetoed
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if a_poll.deposit_held {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps
+                .api
+                .addr_humanize(&config.xprism_token.unwrap())?
+                .to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deps.api.addr_humanize(&a_poll.creator)?.to_string(),
+                amount: a_poll.deposit_amount,
+            })?,
+        }));
+    }
+
+    // the messages are dispatched by the contract to itself in a single
+    // submessage, so a failing message reverts the ones before it
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_submessage(SubMsg {
             msg: CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&execute_data.contract)?.to_string(),
-                msg: execute_data.msg,
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id })?,
                 funds: vec![],
             }),
             gas_limit: Some(config.poll_gas_limit),
             id: POLL_EXECUTE_REPLY_ID,
             reply_on: ReplyOn::Error,
-        });
-        store_tmp_poll_id(deps.storage, a_poll.id)?;
-    } else {
-        return Err(StdError::generic_err("The poll does not have execute_data"));
+        })
+        .add_attributes(vec![
+            attr("action", "execute_poll"),
+            attr("poll_id", poll_id.to_string()),
+        ]))
+}
+
+/*
+ * Dispatches the messages of an executed poll in order
+ */
+pub fn execute_poll_msgs(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> StdResult<Response> {
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let a_poll: Poll = poll_store(deps.storage).load(&poll_id.to_be_bytes())?;
+    if a_poll.status != PollStatus::Executed {
+        return Err(StdError::generic_err("Poll is not in executed status"));
     }
 
     Ok(Response::new()
-        .add_submessages(messages)
+        .add_messages(poll_execute_msgs(deps.api, &a_poll)?)
         .add_attributes(vec![
-            attr("action", "execute_poll"),
+            attr("action", "execute_poll_msgs"),
             attr("poll_id", poll_id.to_string()),
         ]))
 }
 
+/// Choice with strictly the most votes, if any
+fn winning_choice(a_poll: &Poll) -> Option<u32> {
+    let max_votes = a_poll.choices.iter().map(|choice| choice.votes).max()?;
+    let mut winners = a_poll
+        .choices
+        .iter()
+        .enumerate()
+        .filter(|(_, choice)| choice.votes == max_votes);
+
+    match (winners.next(), winners.next()) {
+        (Some((index, _)), None) if !max_votes.is_zero() => Some(index as u32),
+        _ => None,
+    }
+}
+
+/// Messages executed by a poll, including the single message of older polls.
+/// Choice polls execute the messages of their winning choice
+pub fn poll_execute_msgs(api: &dyn Api, a_poll: &Poll) -> StdResult<Vec<CosmosMsg>> {
+    if a_poll.poll_type != PollType::YesNo {
+        return Ok(a_poll
+            .winning_choice
+            .map(|choice| a_poll.choices[choice as usize].execute_msgs.clone())
+            .unwrap_or_default());
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if let Some(execute_data) = &a_poll.execute_data {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&execute_data.contract)?.to_string(),
+            msg: execute_data.msg.clone(),
+            funds: vec![],
+        }));
+    }
+    messages.extend(a_poll.execute_msgs.iter().cloned());
+
+    Ok(messages)
+}
+
 /*
  * If the executed message of a passed poll fails, it is marked as failed
  */
@@ -276,6 +505,7 @@ pub fn cast_vote(
     if a_poll.status != PollStatus::InProgress || current_seconds > a_poll.end_time {
         return Err(StdError::generic_err("Poll is not in progress"));
     }
+    validate_vote(&a_poll, &vote)?;
 
     // Check the voter already has a vote on the poll
     if poll_voter_read(deps.storage, poll_id)
@@ -288,6 +518,28 @@ pub fn cast_vote(
     let key = &sender_address_raw.as_slice();
     let mut token_manager = bank_read(deps.storage).may_load(key)?.unwrap_or_default();
 
+    // a direct vote overrides the vote of the sender's delegate
+    if let Some(delegated_vote) =
+        poll_delegated_vote_read(deps.storage, poll_id).may_load(sender_address_raw.as_slice())?
+    {
+        let delegated_deposit = token_manager
+            .locked_balance
+            .iter()
+            .find(|(locked_poll_id, _)| *locked_poll_id == poll_id)
+            .map(|(_, voter_info)| voter_info.balance)
+            .unwrap_or_default();
+        sub_votes(
+            &mut a_poll,
+            &delegated_vote.vote,
+            delegated_deposit,
+            delegated_vote.balance,
+        )?;
+        token_manager
+            .locked_balance
+            .retain(|(locked_poll_id, _)| *locked_poll_id != poll_id);
+        poll_delegated_vote_store(deps.storage, poll_id).remove(sender_address_raw.as_slice());
+    }
+
     if token_manager.deposit < amount {
         return Err(StdError::generic_err(
             "User does not have enough staked tokens.",
@@ -300,12 +552,13 @@ pub fn cast_vote(
         )));
     }
 
-    // update tally info
-    match vote {
-        VoteOption::Yes => a_poll.yes_votes += amount,
-        VoteOption::No => a_poll.no_votes += amount,
-        VoteOption::Abstain => a_poll.abstain_votes += amount,
-    }
+    // update tally info, locks boost the voting power of the staked amount
+    add_votes(
+        &mut a_poll,
+        &vote,
+        amount,
+        amount_voting_power(&token_manager, amount, current_seconds),
+    );
 
     let vote_info = VoterInfo {
         vote,
@@ -319,6 +572,14 @@ pub fn cast_vote(
     // store poll voter && and update poll data
     poll_voter_store(deps.storage, poll_id).save(sender_address_raw.as_slice(), &vote_info)?;
 
+    let delegated_amount = cast_delegated_votes(
+        deps.storage,
+        &mut a_poll,
+        &sender_address_raw,
+        &vote_info.vote,
+        current_seconds,
+    )?;
+
     // processing snapshot
     let time_to_end = a_poll.end_time - current_seconds;
     if time_to_end < config.snapshot_period && a_poll.supply_snapshot.is_none() {
@@ -337,9 +598,139 @@ pub fn cast_vote(
         attr("amount", &amount.to_string()),
         attr("voter", &info.sender.to_string()),
         attr("vote_option", vote_info.vote.to_string()),
+        attr("delegated_amount", delegated_amount.to_string()),
     ]))
 }
 
+/*
+ * Votes with the staked balance of the delegate's delegators that did not
+ * vote on the poll yet, locking it like their own vote would
+ */
+fn cast_delegated_votes(
+    storage: &mut dyn Storage,
+    a_poll: &mut Poll,
+    delegate: &CanonicalAddr,
+    vote: &VoteOption,
+    current_seconds: u64,
+) -> StdResult<Uint128> {
+    let mut delegated_amount = Uint128::zero();
+    for delegator in read_delegators(storage, delegate, None, None, None)? {
+        let key = delegator.as_slice();
+        if poll_voter_read(storage, a_poll.id).may_load(key)?.is_some()
+            || poll_delegated_vote_read(storage, a_poll.id)
+                .may_load(key)?
+                .is_some()
+        {
+            continue;
+        }
+
+        let mut token_manager = bank_read(storage).may_load(key)?.unwrap_or_default();
+        if token_manager.deposit.is_zero()
+            || token_manager.locked_balance.len() >= MAX_POLL_VOTES_PER_USER
+        {
+            continue;
+        }
+
+        let power = voting_power(&token_manager, current_seconds);
+        add_votes(a_poll, vote, token_manager.deposit, power);
+        token_manager.locked_balance.push((
+            a_poll.id,
+            VoterInfo {
+                vote: vote.clone(),
+                balance: token_manager.deposit,
+            },
+        ));
+        bank_store(storage).save(key, &token_manager)?;
+        poll_delegated_vote_store(storage, a_poll.id).save(
+            key,
+            &DelegatedVote {
+                delegate: delegate.clone(),
+                vote: vote.clone(),
+                balance: power,
+            },
+        )?;
+        delegated_amount += power;
+    }
+
+    Ok(delegated_amount)
+}
+
+/// poll_participation returns the staked amount that voted on the poll
+fn poll_participation(a_poll: &Poll) -> Uint128 {
+    a_poll
+        .participation
+        .unwrap_or(a_poll.yes_votes + a_poll.no_votes + a_poll.abstain_votes + a_poll.choice_votes)
+}
+
+fn add_votes(a_poll: &mut Poll, vote: &VoteOption, deposit: Uint128, amount: Uint128) {
+    a_poll.participation = Some(poll_participation(a_poll) + deposit);
+    match vote {
+        VoteOption::Yes => a_poll.yes_votes += amount,
+        VoteOption::No => a_poll.no_votes += amount,
+        VoteOption::Abstain => a_poll.abstain_votes += amount,
+        VoteOption::Choices(choices) => {
+            for choice in choices.iter() {
+                a_poll.choices[*choice as usize].votes += amount;
+            }
+            a_poll.choice_votes += amount;
+        }
+    }
+}
+
+fn sub_votes(
+    a_poll: &mut Poll,
+    vote: &VoteOption,
+    deposit: Uint128,
+    amount: Uint128,
+) -> StdResult<()> {
+    a_poll.participation = Some(poll_participation(a_poll).checked_sub(deposit)?);
+    match vote {
+        VoteOption::Yes => a_poll.yes_votes = a_poll.yes_votes.checked_sub(amount)?,
+        VoteOption::No => a_poll.no_votes = a_poll.no_votes.checked_sub(amount)?,
+        VoteOption::Abstain => a_poll.abstain_votes = a_poll.abstain_votes.checked_sub(amount)?,
+        VoteOption::Choices(choices) => {
+            for choice in choices.iter() {
+                let poll_choice = &mut a_poll.choices[*choice as usize];
+                poll_choice.votes = poll_choice.votes.checked_sub(amount)?;
+            }
+            a_poll.choice_votes = a_poll.choice_votes.checked_sub(amount)?;
+        }
+    }
+    Ok(())
+}
+
+/// validate_vote returns an error if the vote option does not match the poll type
+fn validate_vote(a_poll: &Poll, vote: &VoteOption) -> StdResult<()> {
+    let valid = match (&a_poll.poll_type, vote) {
+        (_, VoteOption::Abstain) => true,
+        (PollType::YesNo, VoteOption::Yes) => true,
+        (PollType::YesNo, VoteOption::No) => true,
+        (PollType::SingleChoice, VoteOption::Choices(choices)) => choices.len() == 1,
+        (PollType::Approval, VoteOption::Choices(choices)) => {
+            let mut unique = choices.clone();
+            unique.sort_unstable();
+            unique.dedup();
+            !choices.is_empty() && unique.len() == choices.len()
+        }
+        _ => false,
+    };
+
+    if !valid {
+        return Err(StdError::generic_err("Invalid vote option for this poll"));
+    }
+
+    if let VoteOption::Choices(choices) = vote {
+        if choices
+            .iter()
+            .any(|choice| *choice as usize >= a_poll.choices.len())
+        {
+            return Err(StdError::generic_err("Choice does not exist"));
+        }
+    }
+
+    Ok(())
+}
+
 /*
  * SnapshotPoll is used to take a snapshot of the token supply for quorum calculation
  */
@@ -415,3 +806,109 @@ fn validate_link(link: &Option<String>) -> StdResult<()> {
         Ok(())
     }
 }
+
+/// validate_execute_msgs returns an error if a message is not supported or has an invalid address
+fn validate_execute_msgs(api: &dyn Api, execute_msgs: &[CosmosMsg]) -> StdResult<()> {
+    for msg in execute_msgs.iter() {
+        match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. })
+            | CosmosMsg::Wasm(WasmMsg::Migrate { contract_addr, .. })
+            | CosmosMsg::Wasm(WasmMsg::ClearAdmin { contract_addr }) => {
+                api.addr_validate(contract_addr)?;
+            }
+            CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+                contract_addr,
+                admin,
+            }) => {
+                api.addr_validate(contract_addr)?;
+                api.addr_validate(admin)?;
+            }
+            CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => {
+                api.addr_validate(to_address)?;
+            }
+            _ => return Err(StdError::generic_err("Unsupported poll execute message")),
+        }
+    }
+
+    Ok(())
+}
+
+/// validate_choices returns the choices of the poll, or an error if they do not match the poll type
+fn validate_choices(
+    api: &dyn Api,
+    poll_type: &PollType,
+    choices: Option<Vec<PollChoiceMsg>>,
+    execute_msgs: &[CosmosMsg],
+) -> StdResult<Vec<PollChoice>> {
+    let choices = match (poll_type, choices) {
+        (PollType::YesNo, None) => return Ok(vec![]),
+        (PollType::YesNo, Some(_)) => {
+            return Err(StdError::generic_err(
+                "Only single choice and approval polls have choices",
+            ))
+        }
+        (_, choices) => choices.unwrap_or_default(),
+    };
+
+    if !execute_msgs.is_empty() {
+        return Err(StdError::generic_err(
+            "Execute messages of a choice poll are set on its choices",
+        ));
+    }
+
+    if choices.len() < MIN_POLL_CHOICES || choices.len() > MAX_POLL_CHOICES {
+        return Err(StdError::generic_err(format!(
+            "A choice poll must have between {} and {} choices",
+            MIN_POLL_CHOICES, MAX_POLL_CHOICES
+        )));
+    }
+
+    choices
+        .into_iter()
+        .map(|choice| {
+            if choice.description.len() < MIN_CHOICE_LENGTH {
+                return Err(StdError::generic_err("Choice too short"));
+            }
+            if choice.description.len() > MAX_CHOICE_LENGTH {
+                return Err(StdError::generic_err("Choice too long"));
+            }
+
+            let execute_msgs = choice.execute_msgs.unwrap_or_default();
+            validate_execute_msgs(api, &execute_msgs)?;
+
+            Ok(PollChoice {
+                description: choice.description,
+                execute_msgs,
+                votes: Uint128::zero(),
+            })
+        })
+        .collect()
+}
+
+pub fn query_queued_polls(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<QueuedPollsResponse> {
+    let config: Config = config_read(deps.storage).load()?;
+    let polls = read_polls(
+        deps.storage,
+        Some(PollStatus::Passed),
+        start_after,
+        limit,
+        order_by,
+    )?;
+
+    Ok(QueuedPollsResponse {
+        polls: polls
+            .into_iter()
+            .map(|poll| QueuedPollResponse {
+                poll_id: poll.id,
+                title: poll.title,
+                end_time: poll.end_time,
+                earliest_execution_time: poll.end_time + config.effective_delay,
+            })
+            .collect(),
+    })
+}
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Binary, CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{Binary, CanonicalAddr, CosmosMsg, Decimal, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
@@ -7,7 +7,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use prism_protocol::common::OrderBy;
-use prism_protocol::gov::{PollStatus, VoterInfo};
+use prism_protocol::gov::{PollStatus, PollType, VoteOption, VoterInfo};
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_TMP_POLL_ID: &[u8] = b"tmp_poll_id";
@@ -17,6 +17,9 @@ static PREFIX_POLL_INDEXER: &[u8] = b"poll_indexer";
 static PREFIX_POLL_VOTER: &[u8] = b"poll_voter";
 static PREFIX_POLL: &[u8] = b"poll";
 static PREFIX_BANK: &[u8] = b"bank";
+static PREFIX_VOTE_DELEGATION: &[u8] = b"vote_delegation";
+static PREFIX_DELEGATOR: &[u8] = b"delegator";
+static PREFIX_POLL_DELEGATED_VOTE: &[u8] = b"poll_delegated_vote";
 
 pub const MAX_LIMIT: u32 = 30;
 pub const DEFAULT_LIMIT: u32 = 10;
@@ -34,12 +37,25 @@ pub struct Config {
     pub snapshot_period: u64,
     pub redemption_time: u64,
     pub poll_gas_limit: u64,
+    #[serde(default)]
+    pub instant_redemption_fee: Option<Decimal>,
+    #[serde(default)]
+    pub guardian: Option<CanonicalAddr>,
 }
 
 #[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct VotingTokenManager {
     pub deposit: Uint128,                      // total staked balance
     pub locked_balance: Vec<(u64, VoterInfo)>, // maps poll_id to balance voted
+    #[serde(default)]
+    pub locks: Vec<VotingLock>, // staked balance locked for a voting power boost
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingLock {
+    pub amount: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -54,11 +70,46 @@ pub struct Poll {
     pub title: String,
     pub description: String,
     pub link: Option<String>,
+    /// Single message of polls created before multi-message execution
     pub execute_data: Option<ExecuteData>,
+    #[serde(default)]
+    pub execute_msgs: Vec<CosmosMsg>,
     pub deposit_amount: Uint128,
     pub supply_snapshot: Option<Uint128>,
     pub required_quorum: Decimal,
     pub required_threshold: Decimal,
+    #[serde(default)]
+    pub poll_type: PollType,
+    #[serde(default)]
+    pub choices: Vec<PollChoice>,
+    /// Voting power cast on choices, counted once per voter
+    #[serde(default)]
+    pub choice_votes: Uint128,
+    #[serde(default)]
+    pub winning_choice: Option<u32>,
+    /// Staked amount that took part in the poll, which counts for quorum
+    /// instead of the boosted voting power. Not tracked by polls that got
+    /// no vote since locks were introduced, whose votes are all unboosted
+    #[serde(default)]
+    pub participation: Option<Uint128>,
+    /// Deposit of a passed poll kept until it is executed or vetoed
+    #[serde(default)]
+    pub deposit_held: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollChoice {
+    pub description: String,
+    pub execute_msgs: Vec<CosmosMsg>,
+    pub votes: Uint128,
+}
+
+/// Vote cast by a delegate with the staked balance of a delegator
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegatedVote {
+    pub delegate: CanonicalAddr,
+    pub vote: VoteOption,
+    pub balance: Uint128, // voting power added to the tally
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -193,6 +244,81 @@ pub fn bank_read(storage: &dyn Storage) -> ReadonlyBucket<VotingTokenManager> {
     bucket_read(storage, PREFIX_BANK)
 }
 
+/// delegator -> delegate
+pub fn vote_delegation_store(storage: &mut dyn Storage) -> Bucket<CanonicalAddr> {
+    bucket(storage, PREFIX_VOTE_DELEGATION)
+}
+
+pub fn vote_delegation_read(storage: &dyn Storage) -> ReadonlyBucket<CanonicalAddr> {
+    bucket_read(storage, PREFIX_VOTE_DELEGATION)
+}
+
+/// delegators of a delegate
+pub fn delegator_store<'a>(
+    storage: &'a mut dyn Storage,
+    delegate: &CanonicalAddr,
+) -> Bucket<'a, bool> {
+    Bucket::multilevel(storage, &[PREFIX_DELEGATOR, delegate.as_slice()])
+}
+
+pub fn read_delegators<'a>(
+    storage: &'a dyn Storage,
+    delegate: &CanonicalAddr,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (calc_range_start_addr(start_after), None, OrderBy::Asc),
+        _ => (None, calc_range_end_addr(start_after), OrderBy::Desc),
+    };
+
+    let delegators: ReadonlyBucket<'a, bool> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_DELEGATOR, delegate.as_slice()]);
+    let delegators = delegators.range(start.as_deref(), end.as_deref(), order_by.into());
+    match limit {
+        Some(limit) => delegators
+            .take(limit.min(MAX_LIMIT) as usize)
+            .map(|item| Ok(CanonicalAddr::from(item?.0)))
+            .collect(),
+        None => delegators
+            .map(|item| Ok(CanonicalAddr::from(item?.0)))
+            .collect(),
+    }
+}
+
+/// votes cast by delegates on a poll, keyed by delegator
+pub fn poll_delegated_vote_store(storage: &mut dyn Storage, poll_id: u64) -> Bucket<DelegatedVote> {
+    Bucket::multilevel(
+        storage,
+        &[PREFIX_POLL_DELEGATED_VOTE, &poll_id.to_be_bytes()],
+    )
+}
+
+/// Removes the votes cast by delegates on a poll that is no longer in progress
+pub fn remove_poll_delegated_votes(storage: &mut dyn Storage, poll_id: u64) -> StdResult<()> {
+    let delegators = poll_delegated_vote_read(storage, poll_id)
+        .range(None, None, Order::Ascending)
+        .map(|item| item.map(|(k, _)| k))
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+
+    let mut delegated_votes = poll_delegated_vote_store(storage, poll_id);
+    for delegator in delegators.iter() {
+        delegated_votes.remove(delegator);
+    }
+    Ok(())
+}
+
+pub fn poll_delegated_vote_read(
+    storage: &dyn Storage,
+    poll_id: u64,
+) -> ReadonlyBucket<DelegatedVote> {
+    ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_POLL_DELEGATED_VOTE, &poll_id.to_be_bytes()],
+    )
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 pub fn calc_range_start(start_after: Option<u64>) -> Option<Vec<u8>> {
     start_after.map(|id| {
@@ -1,22 +1,26 @@
 use crate::contract::{execute, instantiate, query, reply, MIN_POLL_GAS_LIMIT};
 use crate::polls::MAX_POLL_VOTES_PER_USER;
 use crate::state::{
-    bank_read, bank_store, config_read, poll_store, poll_voter_read, poll_voter_store, Config,
-    Poll, VotingTokenManager,
+    bank_read, bank_store, config_read, poll_delegated_vote_read, poll_store, poll_voter_read,
+    poll_voter_store, Config, ExecuteData, Poll, VotingTokenManager,
 };
+use crate::voting::{MAX_LOCK_DURATION, MIN_LOCK_DURATION};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coins, from_binary, to_binary, Addr, Api, CanonicalAddr, ContractResult, CosmosMsg,
-    Decimal, DepsMut, Env, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse,
-    Timestamp, Uint128, WasmMsg,
+    attr, coins, from_binary, to_binary, Addr, Api, BankMsg, Binary, CanonicalAddr, ContractResult,
+    CosmosMsg, Decimal, DepsMut, Env, Reply, ReplyOn, Response, StdError, SubMsg,
+    SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 use prism_common::testing::mock_querier::mock_dependencies;
 use prism_protocol::common::OrderBy;
 use prism_protocol::gov::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PollExecuteMsg, PollResponse,
-    PollStatus, PollsResponse, PrismWithdrawOrdersResponse, QueryMsg, VoteOption, VoterInfo,
-    VotersResponse, VotersResponseItem, VotingTokensResponse, XprismStateResponse,
+    ConfigResponse, Cw20HookMsg, DelegatedPowerResponse, DelegatorsResponse,
+    DelegatorsResponseItem, ExecuteMsg, InstantiateMsg, LockPositionResponse,
+    LockPositionsResponse, PollChoiceMsg, PollResponse, PollStatus, PollType, PollsResponse,
+    PrismWithdrawOrdersResponse, QueryMsg, QueuedPollResponse, QueuedPollsResponse,
+    VoteDelegationResponse, VoteOption, VoterInfo, VotersResponse, VotersResponseItem,
+    VotingPowerResponse, VotingTokensResponse, XprismStateResponse,
 };
 use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
 
@@ -48,6 +52,8 @@ fn mock_instantiate(deps: DepsMut) {
         redemption_time: DEFAULT_REDEMPTION_TIME,
         poll_gas_limit: DEFAULT_POLL_GAS_LIMIT,
         token_code_id: DEFAULT_TOKEN_CODE_ID,
+        instant_redemption_fee: None,
+        guardian: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -85,6 +91,8 @@ fn init_msg() -> InstantiateMsg {
         redemption_time: DEFAULT_REDEMPTION_TIME,
         poll_gas_limit: DEFAULT_POLL_GAS_LIMIT,
         token_code_id: DEFAULT_TOKEN_CODE_ID,
+        instant_redemption_fee: None,
+        guardian: None,
     }
 }
 
@@ -164,6 +172,8 @@ fn proper_initialization() {
             snapshot_period: DEFAULT_SNAPSHOT_PERIOD,
             redemption_time: DEFAULT_REDEMPTION_TIME,
             poll_gas_limit: DEFAULT_POLL_GAS_LIMIT,
+            instant_redemption_fee: None,
+            guardian: None,
         }
     );
 }
@@ -321,7 +331,9 @@ fn fails_create_poll_invalid_deposit() {
             title: "TESTTEST".to_string(),
             description: "TESTTEST".to_string(),
             link: None,
-            execute_msg: None,
+            execute_msgs: None,
+            poll_type: None,
+            choices: None,
         })
         .unwrap(),
     });
@@ -340,7 +352,7 @@ fn create_poll_msg(
     title: String,
     description: String,
     link: Option<String>,
-    execute_msg: Option<PollExecuteMsg>,
+    execute_msgs: Option<Vec<CosmosMsg>>,
 ) -> ExecuteMsg {
     ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_CREATOR.to_string(),
@@ -349,7 +361,9 @@ fn create_poll_msg(
             title,
             description,
             link,
-            execute_msg,
+            execute_msgs,
+            poll_type: None,
+            choices: None,
         })
         .unwrap(),
     })
@@ -416,13 +430,16 @@ fn query_polls() {
                 description: "test".to_string(),
                 link: Some("http://google.com".to_string()),
                 deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
-                execute_data: None,
+                execute_msgs: vec![],
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
                 abstain_votes: Uint128::zero(),
                 supply_snapshot: None,
                 required_quorum: Decimal::percent(DEFAULT_QUORUM),
                 required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+                poll_type: PollType::YesNo,
+                choices: vec![],
+                winning_choice: None,
             },
             PollResponse {
                 id: 2u64,
@@ -433,13 +450,16 @@ fn query_polls() {
                 description: "test2".to_string(),
                 link: None,
                 deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
-                execute_data: None,
+                execute_msgs: vec![],
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
                 abstain_votes: Uint128::zero(),
                 supply_snapshot: None,
                 required_quorum: Decimal::percent(DEFAULT_QUORUM),
                 required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+                poll_type: PollType::YesNo,
+                choices: vec![],
+                winning_choice: None,
             },
         ]
     );
@@ -467,13 +487,16 @@ fn query_polls() {
             description: "test2".to_string(),
             link: None,
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
-            execute_data: None,
+            execute_msgs: vec![],
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
             abstain_votes: Uint128::zero(),
             supply_snapshot: None,
             required_quorum: Decimal::percent(DEFAULT_QUORUM),
             required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+            poll_type: PollType::YesNo,
+            choices: vec![],
+            winning_choice: None,
         },]
     );
 
@@ -500,13 +523,16 @@ fn query_polls() {
             description: "test".to_string(),
             link: Some("http://google.com".to_string()),
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
-            execute_data: None,
+            execute_msgs: vec![],
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
             abstain_votes: Uint128::zero(),
             supply_snapshot: None,
             required_quorum: Decimal::percent(DEFAULT_QUORUM),
             required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+            poll_type: PollType::YesNo,
+            choices: vec![],
+            winning_choice: None,
         }]
     );
 
@@ -533,13 +559,16 @@ fn query_polls() {
             description: "test2".to_string(),
             link: None,
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
-            execute_data: None,
+            execute_msgs: vec![],
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
             abstain_votes: Uint128::zero(),
             supply_snapshot: None,
             required_quorum: Decimal::percent(DEFAULT_QUORUM),
             required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+            poll_type: PollType::YesNo,
+            choices: vec![],
+            winning_choice: None,
         },]
     );
 
@@ -621,10 +650,11 @@ fn happy_days_end_poll() {
         "test".to_string(),
         "test".to_string(),
         None,
-        Some(PollExecuteMsg {
-            contract: VOTING_TOKEN.to_string(),
+        Some(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz.clone(),
-        }),
+            funds: vec![],
+        })]),
     );
 
     let execute_res = execute(
@@ -681,6 +711,7 @@ fn happy_days_end_poll() {
             attr("amount", stake_amount.to_string()),
             attr("voter", TEST_VOTER),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -757,13 +788,19 @@ fn happy_days_end_poll() {
 
     creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_EFFECTIVE_DELAY);
     let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
-    let execute_res = execute(deps.as_mut(), creator_env, creator_info, msg).unwrap();
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info.clone(),
+        msg,
+    )
+    .unwrap();
     assert_eq!(
         execute_res.messages,
         vec![SubMsg {
             msg: CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 1 }).unwrap(),
                 funds: vec![],
             }),
             gas_limit: Some(DEFAULT_POLL_GAS_LIMIT),
@@ -776,6 +813,40 @@ fn happy_days_end_poll() {
         vec![attr("action", "execute_poll"), attr("poll_id", "1"),]
     );
 
+    // only the contract itself can dispatch the poll messages
+    let msg = ExecuteMsg::ExecutePollMsgs { poll_id: 1 };
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env.clone(),
+        creator_info,
+        msg.clone(),
+    )
+    .unwrap_err();
+    match execute_res {
+        StdError::GenericErr { msg, .. } => assert_eq!(msg, "unauthorized"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz,
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        execute_res.attributes,
+        vec![attr("action", "execute_poll_msgs"), attr("poll_id", "1")]
+    );
+
     // Query executed polls
     let res = query(
         deps.as_ref(),
@@ -858,10 +929,11 @@ fn failed_execute_poll() {
         "test".to_string(),
         "test".to_string(),
         None,
-        Some(PollExecuteMsg {
-            contract: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz.clone(),
-        }),
+        Some(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: exec_msg_bz,
+            funds: vec![],
+        })]),
     );
 
     let execute_res = execute(
@@ -918,6 +990,7 @@ fn failed_execute_poll() {
             attr("amount", stake_amount.to_string()),
             attr("voter", TEST_VOTER),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -961,8 +1034,8 @@ fn failed_execute_poll() {
         execute_res.messages,
         vec![SubMsg {
             msg: CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 1 }).unwrap(),
                 funds: vec![],
             }),
             gas_limit: Some(DEFAULT_POLL_GAS_LIMIT),
@@ -1001,6 +1074,109 @@ fn failed_execute_poll() {
     assert_eq!(polls_res.polls[0], poll_res);
 }
 
+#[test]
+fn happy_days_multi_message_poll() {
+    let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+    let env = mock_env_height(0, 10000);
+    let info = mock_info(VOTING_TOKEN, &[]);
+
+    // unsupported message kind
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(vec![CosmosMsg::Wasm(WasmMsg::Instantiate {
+            admin: None,
+            code_id: 1,
+            msg: Binary::default(),
+            funds: vec![],
+            label: "".to_string(),
+        })]),
+    );
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        StdError::generic_err("Unsupported poll execute message")
+    );
+
+    let execute_msgs = vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "vault0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(123),
+            })
+            .unwrap(),
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: "collector0000".to_string(),
+            new_code_id: 2,
+            msg: Binary::default(),
+        }),
+        CosmosMsg::Wasm(WasmMsg::UpdateAdmin {
+            contract_addr: "launchpool0000".to_string(),
+            admin: "newadmin0000".to_string(),
+        }),
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: TEST_VOTER.to_string(),
+            amount: coins(100, "uusd"),
+        }),
+    ];
+    let msg = create_poll_msg(
+        "test".to_string(),
+        "test".to_string(),
+        None,
+        Some(execute_msgs.clone()),
+    );
+    let execute_res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_create_poll_result(
+        1,
+        env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD).seconds(),
+        TEST_CREATOR,
+        execute_res,
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.execute_msgs, execute_msgs);
+
+    // messages of polls created with a single message are dispatched first
+    let legacy_msg = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(1),
+    })
+    .unwrap();
+    let mut a_poll = poll_store(&mut deps.storage)
+        .load(&1u64.to_be_bytes())
+        .unwrap();
+    a_poll.status = PollStatus::Executed;
+    a_poll.execute_data = Some(ExecuteData {
+        contract: deps.api.addr_canonicalize(VOTING_TOKEN).unwrap(),
+        msg: legacy_msg.clone(),
+    });
+    poll_store(&mut deps.storage)
+        .save(&1u64.to_be_bytes(), &a_poll)
+        .unwrap();
+
+    let msg = ExecuteMsg::ExecutePollMsgs { poll_id: 1 };
+    let execute_res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+
+    let mut expected_msgs = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: VOTING_TOKEN.to_string(),
+        msg: legacy_msg,
+        funds: vec![],
+    })];
+    expected_msgs.extend(execute_msgs);
+    assert_eq!(
+        execute_res.messages,
+        expected_msgs
+            .into_iter()
+            .map(SubMsg::new)
+            .collect::<Vec<SubMsg>>()
+    );
+}
+
 #[test]
 fn end_poll_zero_quorum() {
     let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
@@ -1013,13 +1189,14 @@ fn end_poll_zero_quorum() {
         "test".to_string(),
         "test".to_string(),
         None,
-        Some(PollExecuteMsg {
-            contract: VOTING_TOKEN.to_string(),
+        Some(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Burn {
                 amount: Uint128::new(123),
             })
             .unwrap(),
-        }),
+            funds: vec![],
+        })]),
     );
 
     let execute_res = execute(
@@ -1194,6 +1371,7 @@ fn end_poll_quorum_rejected() {
             attr("amount", "10"),
             attr("voter", TEST_VOTER),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -1630,104 +1808,1354 @@ fn happy_days_cast_vote() {
         },
     )
     .unwrap();
-    let response: VotersResponse = from_binary(&res).unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: TEST_VOTER.to_string(),
+            vote: VoteOption::Yes,
+            balance: Uint128::from(10u128),
+        }]
+    );
+}
+
+#[test]
+fn happy_days_delegate_votes() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    let env = mock_env_height(0, 0);
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(deps.as_mut(), env, info, msg).unwrap();
+
+    for (voter, amount) in [(TEST_VOTER, 10u128), (TEST_VOTER_2, 20), (TEST_VOTER_3, 30)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::DelegateVotes {
+        delegate: TEST_VOTER.to_string(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Can not delegate votes to yourself")
+    );
+    for delegator in [TEST_VOTER_2, TEST_VOTER_3] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(delegator, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.attributes,
+            vec![
+                attr("action", "delegate_votes"),
+                attr("delegator", delegator),
+                attr("delegate", TEST_VOTER),
+            ]
+        );
+    }
+
+    let res: DelegatedPowerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DelegatedPower {
+                delegate: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        DelegatedPowerResponse {
+            delegate: TEST_VOTER.to_string(),
+            delegated_power: Uint128::new(50),
+            delegators: 2,
+        }
+    );
+    let res: DelegatorsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Delegators {
+                delegate: TEST_VOTER.to_string(),
+                start_after: None,
+                limit: None,
+                order_by: Some(OrderBy::Asc),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.delegators,
+        vec![
+            DelegatorsResponseItem {
+                delegator: TEST_VOTER_2.to_string(),
+                balance: Uint128::new(20),
+            },
+            DelegatorsResponseItem {
+                delegator: TEST_VOTER_3.to_string(),
+                balance: Uint128::new(30),
+            },
+        ]
+    );
+
+    // a delegator that already voted is not counted for the delegate
+    let env = mock_env_height(0, 10);
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: Uint128::new(30),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_3, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Uint128::new(10),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert!(res.attributes.contains(&attr("delegated_amount", "20")));
+
+    let poll: PollResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(poll.yes_votes, Uint128::new(30));
+    assert_eq!(poll.no_votes, Uint128::new(30));
+
+    // the delegated balance is locked like a direct vote
+    let res: VotingTokensResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VotingTokens {
+                address: TEST_VOTER_2.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.locked_balance,
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128::new(20),
+            }
+        )]
+    );
+
+    // voting directly overrides the delegate's vote
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::No,
+        amount: Uint128::new(15),
+    };
+    execute(deps.as_mut(), env, mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+
+    let poll: PollResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(poll.yes_votes, Uint128::new(10));
+    assert_eq!(poll.no_votes, Uint128::new(45));
+    let res: VotingTokensResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VotingTokens {
+                address: TEST_VOTER_2.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.locked_balance,
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::No,
+                balance: Uint128::new(15),
+            }
+        )]
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::UndelegateVotes {},
+    )
+    .unwrap();
+    assert!(res.attributes.contains(&attr("action", "undelegate_votes")));
+    let res: VoteDelegationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VoteDelegation {
+                address: TEST_VOTER_2.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegate, None);
+    let res: DelegatedPowerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::DelegatedPower {
+                delegate: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegated_power, Uint128::new(30));
+    assert_eq!(res.delegators, 1);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::UndelegateVotes {},
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Votes are not delegated"));
+}
+
+#[test]
+fn delegated_votes_removed_with_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    let env = mock_env_height(0, 0);
+    for _ in 0..2 {
+        let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    for (voter, amount) in [(TEST_VOTER, 10u128), (TEST_VOTER_2, 20)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+    let msg = ExecuteMsg::DelegateVotes {
+        delegate: TEST_VOTER.to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+
+    for poll_id in [1u64, 2] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Uint128::new(10),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    }
+    let delegator = deps.api.addr_canonicalize(TEST_VOTER_2).unwrap();
+    for poll_id in [1u64, 2] {
+        assert!(poll_delegated_vote_read(&deps.storage, poll_id)
+            .may_load(delegator.as_slice())
+            .unwrap()
+            .is_some());
+    }
+
+    // the delegated votes are removed when the poll is cancelled or ended
+    let msg = ExecuteMsg::CancelPoll { poll_id: 2 };
+    execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert!(poll_delegated_vote_read(&deps.storage, 2)
+        .may_load(delegator.as_slice())
+        .unwrap()
+        .is_none());
+    assert!(poll_delegated_vote_read(&deps.storage, 1)
+        .may_load(delegator.as_slice())
+        .unwrap()
+        .is_some());
+
+    let env = mock_env_height(0, DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert!(poll_delegated_vote_read(&deps.storage, 1)
+        .may_load(delegator.as_slice())
+        .unwrap()
+        .is_none());
+
+    // the delegated stake is unlocked again
+    let res: VotingTokensResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VotingTokens {
+                address: TEST_VOTER_2.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.locked_balance, vec![]);
+}
+
+#[test]
+fn happy_days_cancel_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    // polls 1 and 2 are created by a voter, the owner is TEST_CREATOR
+    for _ in 0..2 {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: TEST_VOTER_2.to_string(),
+            amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            msg: to_binary(&Cw20HookMsg::CreatePoll {
+                title: "test".to_string(),
+                description: "test".to_string(),
+                link: None,
+                execute_msgs: None,
+                poll_type: None,
+                choices: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::new(100),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 2,
+        vote: VoteOption::Yes,
+        amount: Uint128::new(100),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    // only the creator or the owner can cancel
+    let msg = ExecuteMsg::CancelPoll { poll_id: 1 };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // the creator gets the deposit back if there are no votes
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "cancel_poll"),
+            attr("poll_id", "1"),
+            attr("deposit_refunded", "true"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER_2.to_string(),
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let err = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Poll is not in progress"));
+
+    // the creator can not cancel once votes are cast
+    let msg = ExecuteMsg::CancelPoll { poll_id: 2 };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // the owner can, and the deposit is burned
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "cancel_poll"),
+            attr("poll_id", "2"),
+            attr("deposit_refunded", "false"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let res: PollsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Polls {
+                filter: Some(PollStatus::Cancelled),
+                start_after: None,
+                limit: None,
+                order_by: Some(OrderBy::Asc),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.polls.iter().map(|poll| poll.id).collect::<Vec<u64>>(),
+        vec![1, 2]
+    );
+
+    // the votes on the cancelled poll are unlocked
+    let res: VotingTokensResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::VotingTokens {
+                address: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.locked_balance, vec![]);
+
+    let msg = ExecuteMsg::WithdrawVotingTokens { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn happy_days_veto_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        effective_delay: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        redemption_time: None,
+        poll_gas_limit: None,
+        instant_redemption_fee: None,
+        guardian: Some("guardian0000".to_string()),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let execute_msgs = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: VOTING_TOKEN.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: Uint128::new(123),
+        })
+        .unwrap(),
+        funds: vec![],
+    })];
+    let env = mock_env_height(0, 0);
+    for _ in 0..2 {
+        let msg = create_poll_msg(
+            "test".to_string(),
+            "test".to_string(),
+            None,
+            Some(execute_msgs.clone()),
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::new(100),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
+    )]);
+
+    let mut env = env;
+    for poll_id in 1..=2 {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Yes,
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    }
+
+    // the deposits of passed polls are held while the guardian can veto them
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    for poll_id in 1..=2 {
+        let msg = ExecuteMsg::EndPoll { poll_id };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(TEST_CREATOR, &[]),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(res.attributes[3], attr("passed", "true"));
+        assert!(res.messages.is_empty());
+    }
+
+    let res: QueuedPollsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::QueuedPolls {
+                start_after: None,
+                limit: None,
+                order_by: Some(OrderBy::Asc),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let end_time = env.block.time.seconds();
+    assert_eq!(
+        res.polls,
+        vec![
+            QueuedPollResponse {
+                poll_id: 1,
+                title: "test".to_string(),
+                end_time,
+                earliest_execution_time: end_time + DEFAULT_EFFECTIVE_DELAY,
+            },
+            QueuedPollResponse {
+                poll_id: 2,
+                title: "test".to_string(),
+                end_time,
+                earliest_execution_time: end_time + DEFAULT_EFFECTIVE_DELAY,
+            },
+        ]
+    );
+
+    // only the guardian can veto, and the deposit is burned
+    let msg = ExecuteMsg::VetoPoll { poll_id: 1 };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("guardian0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "veto_poll"),
+            attr("poll_id", "1"),
+            attr("deposit_burned", "true"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("guardian0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Poll is not in passed status"));
+
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Poll is not in passed status"));
+
+    // once the effective delay expires the poll can no longer be vetoed,
+    // and the deposit is refunded on execution
+    env.block.time = env.block.time.plus_seconds(DEFAULT_EFFECTIVE_DELAY);
+    let msg = ExecuteMsg::VetoPoll { poll_id: 2 };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("guardian0000", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Effective delay has expired"));
+
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 2 };
+    let res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_CREATOR.to_string(),
+                    amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg {
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                    msg: to_binary(&ExecuteMsg::ExecutePollMsgs { poll_id: 2 }).unwrap(),
+                    funds: vec![],
+                }),
+                gas_limit: Some(DEFAULT_POLL_GAS_LIMIT),
+                id: 1u64,
+                reply_on: ReplyOn::Error,
+            },
+        ]
+    );
+
+    let res: QueuedPollsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::QueuedPolls {
+                start_after: None,
+                limit: None,
+                order_by: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.polls, vec![]);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Vetoed);
+
+    // only the owner can remove the guardian, which can not veto anymore
+    let msg = ExecuteMsg::RemoveGuardian {};
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.guardian, None);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("guardian0000", &[]),
+        ExecuteMsg::VetoPoll { poll_id: 2 },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+}
+
+fn create_choice_poll_msg(poll_type: PollType, choices: Vec<PollChoiceMsg>) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs: None,
+            poll_type: Some(poll_type),
+            choices: Some(choices),
+        })
+        .unwrap(),
+    })
+}
+
+fn mock_choices() -> Vec<PollChoiceMsg> {
+    ["5%", "10%", "15%"]
+        .iter()
+        .map(|fee| PollChoiceMsg {
+            description: fee.to_string(),
+            execute_msgs: Some(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "collector0000".to_string(),
+                msg: to_binary(&fee.to_string()).unwrap(),
+                funds: vec![],
+            })]),
+        })
+        .collect()
+}
+
+fn mock_stake_voters(mut deps: DepsMut) {
+    for (voter, amount) in [(TEST_VOTER, 50u128), (TEST_VOTER_2, 30), (TEST_VOTER_3, 20)] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: voter.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        });
+        execute(deps.branch(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    }
+}
+
+#[test]
+fn fails_create_choice_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+    let info = mock_info(VOTING_TOKEN, &[]);
+
+    let msg = create_choice_poll_msg(PollType::YesNo, mock_choices());
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Only single choice and approval polls have choices")
+    );
+
+    let msg = create_choice_poll_msg(PollType::SingleChoice, mock_choices()[..1].to_vec());
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("A choice poll must have between 2 and 10 choices")
+    );
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs: Some(vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: TEST_VOTER.to_string(),
+                amount: coins(100, "uusd"),
+            })]),
+            poll_type: Some(PollType::Approval),
+            choices: Some(mock_choices()),
+        })
+        .unwrap(),
+    });
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Execute messages of a choice poll are set on its choices")
+    );
+}
+
+#[test]
+fn happy_days_single_choice_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+    mock_stake_voters(deps.as_mut());
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
+    )]);
+
+    let env = mock_env_height(0, 0);
+    let msg = create_choice_poll_msg(PollType::SingleChoice, mock_choices());
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    for (vote, err) in [
+        (VoteOption::Yes, "Invalid vote option for this poll"),
+        (
+            VoteOption::Choices(vec![0, 1]),
+            "Invalid vote option for this poll",
+        ),
+        (VoteOption::Choices(vec![3]), "Choice does not exist"),
+    ] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id: 1,
+            vote,
+            amount: Uint128::new(50),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+        assert_eq!(res.unwrap_err(), StdError::generic_err(err));
+    }
+
+    for (voter, vote, amount) in [
+        (TEST_VOTER, VoteOption::Choices(vec![1]), 50u128),
+        (TEST_VOTER_2, VoteOption::Choices(vec![0]), 30),
+        (TEST_VOTER_3, VoteOption::Abstain, 20),
+    ] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id: 1,
+            vote: vote.clone(),
+            amount: Uint128::from(amount),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        assert_eq!(res.attributes[4], attr("vote_option", vote.to_string()));
+    }
+
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("passed", "true"));
+
+    // 50 of the 80 votes cast on choices, abstain votes only count for quorum
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Passed);
+    assert_eq!(poll_res.poll_type, PollType::SingleChoice);
+    assert_eq!(poll_res.winning_choice, Some(1));
+    assert_eq!(poll_res.abstain_votes, Uint128::new(20));
+    assert_eq!(
+        poll_res
+            .choices
+            .iter()
+            .map(|choice| choice.votes)
+            .collect::<Vec<Uint128>>(),
+        vec![Uint128::new(30), Uint128::new(50), Uint128::zero()]
+    );
+    let winning_msgs = mock_choices()[1].execute_msgs.clone().unwrap();
+    assert_eq!(poll_res.execute_msgs, winning_msgs);
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_EFFECTIVE_DELAY);
+    let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::ExecutePollMsgs { poll_id: 1 };
+    let res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        winning_msgs
+            .into_iter()
+            .map(SubMsg::new)
+            .collect::<Vec<SubMsg>>()
+    );
+}
+
+#[test]
+fn happy_days_approval_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+    mock_stake_voters(deps.as_mut());
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
+    )]);
+
+    let mut env = mock_env_height(0, 0);
+    for _ in 0..2 {
+        let msg = create_choice_poll_msg(PollType::Approval, mock_choices());
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Choices(vec![1, 1]),
+        amount: Uint128::new(50),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        StdError::generic_err("Invalid vote option for this poll")
+    );
+
+    // each approved choice gets the full amount
+    for (poll_id, voter, choices, amount) in [
+        (1, TEST_VOTER, vec![0, 1], 50u128),
+        (1, TEST_VOTER_2, vec![1], 30),
+        (1, TEST_VOTER_3, vec![2, 0], 20),
+        (2, TEST_VOTER, vec![0, 2], 50),
+        (2, TEST_VOTER_2, vec![2, 0], 30),
+    ] {
+        let msg = ExecuteMsg::CastVote {
+            poll_id,
+            vote: VoteOption::Choices(choices),
+            amount: Uint128::from(amount),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+    }
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("passed", "true"));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.winning_choice, Some(1));
+    assert_eq!(
+        poll_res
+            .choices
+            .iter()
+            .map(|choice| choice.votes)
+            .collect::<Vec<Uint128>>(),
+        vec![Uint128::new(70), Uint128::new(80), Uint128::new(20)]
+    );
+
+    // choices 0 and 2 are tied
+    let msg = ExecuteMsg::EndPoll { poll_id: 2 };
+    let res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "2"),
+            attr("rejected_reason", "No winning choice"),
+            attr("passed", "false"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 2 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Rejected);
+    assert_eq!(poll_res.winning_choice, None);
+    assert_eq!(poll_res.execute_msgs, vec![]);
+}
+
+#[test]
+fn happy_days_withdraw_voting_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(11u128))],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_stake_tokens_result(11, execute_res);
+
+    // double the balance, only half will be withdrawn
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(22u128))],
+    )]);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::from(11u128)),
+    };
+
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = execute_res.messages.get(0).expect("no message");
+
+    assert_eq!(
+        msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(11u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+}
+
+#[test]
+fn happy_days_withdraw_voting_tokens_all() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(11u128))],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_stake_tokens_result(11, execute_res);
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::WithdrawVotingTokens { amount: None };
+
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = execute_res.messages.get(0).expect("no message");
+
+    assert_eq!(
+        msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::from(11u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+}
+
+#[test]
+fn happy_days_lock_voting_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+
+    let mut env = mock_env_height(0, 1000);
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::LockVotingTokens {
+        amount: Uint128::new(400),
+        duration: MIN_LOCK_DURATION - 1,
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Lock duration must be between {} and {} seconds",
+            MIN_LOCK_DURATION, MAX_LOCK_DURATION
+        ))
+    );
+
+    let msg = ExecuteMsg::LockVotingTokens {
+        amount: Uint128::new(400),
+        duration: MAX_LOCK_DURATION,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock_voting_tokens"),
+            attr("sender", TEST_VOTER),
+            attr("amount", "400"),
+            attr("end_time", (1000 + MAX_LOCK_DURATION).to_string()),
+        ]
+    );
+
+    let msg = ExecuteMsg::LockVotingTokens {
+        amount: Uint128::new(601),
+        duration: MIN_LOCK_DURATION,
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("User does not have enough unlocked staked tokens")
+    );
+
+    // a full length lock gives the maximum boost
+    let res: VotingPowerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VotingPower {
+                address: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        VotingPowerResponse {
+            balance: Uint128::new(1000),
+            locked_amount: Uint128::new(400),
+            voting_power: Uint128::new(1600),
+        }
+    );
+
+    // the boost decays linearly
+    env.block.time = env.block.time.plus_seconds(MAX_LOCK_DURATION / 2);
+    let res: LockPositionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::LockPositions {
+                address: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.locks,
+        vec![LockPositionResponse {
+            amount: Uint128::new(400),
+            start_time: 1000,
+            end_time: 1000 + MAX_LOCK_DURATION,
+            multiplier: Decimal::from_ratio(175u128, 100u128),
+        }]
+    );
+
+    // locked tokens can not be withdrawn
+    let msg = ExecuteMsg::WithdrawVotingTokens {
+        amount: Some(Uint128::new(601)),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("User is trying to withdraw too many tokens")
+    );
+    let msg = ExecuteMsg::WithdrawVotingTokens { amount: None };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.attributes[2], attr("amount", "600"));
+
+    // votes count with the boosted voting power, 400 * 1.75
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Uint128::new(400),
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.yes_votes, Uint128::new(700));
+
+    // expired locks do not boost nor lock anymore
+    env.block.time = Timestamp::from_seconds(1000 + MAX_LOCK_DURATION);
+    let res: VotingPowerResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::VotingPower {
+                address: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
     assert_eq!(
-        response.voters,
-        vec![VotersResponseItem {
-            voter: TEST_VOTER.to_string(),
-            vote: VoteOption::Yes,
-            balance: Uint128::from(10u128),
-        }]
+        res,
+        VotingPowerResponse {
+            balance: Uint128::new(400),
+            locked_amount: Uint128::zero(),
+            voting_power: Uint128::new(400),
+        }
     );
+
+    let res: LockPositionsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::LockPositions {
+                address: TEST_VOTER.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.locks, vec![]);
 }
 
 #[test]
-fn happy_days_withdraw_voting_tokens() {
+fn end_poll_quorum_ignores_lock_boost() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     mock_reply(deps.as_mut());
 
-    deps.querier.with_token_balances(&[(
-        &VOTING_TOKEN.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(11u128))],
-    )]);
-
+    let env = mock_env_height(0, 1000);
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(11u128),
+        amount: Uint128::new(20),
         msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
     });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
 
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_stake_tokens_result(11, execute_res);
-
-    // double the balance, only half will be withdrawn
-    deps.querier.with_token_balances(&[(
-        &VOTING_TOKEN.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(22u128))],
-    )]);
-
-    let info = mock_info(TEST_VOTER, &[]);
-    let msg = ExecuteMsg::WithdrawVotingTokens {
-        amount: Some(Uint128::from(11u128)),
+    let msg = ExecuteMsg::LockVotingTokens {
+        amount: Uint128::new(20),
+        duration: MAX_LOCK_DURATION,
     };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
 
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let msg = execute_res.messages.get(0).expect("no message");
-
-    assert_eq!(
+    let msg = create_poll_msg("test".to_string(), "test".to_string(), None, None);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
         msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: VOTING_TOKEN.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: TEST_VOTER.to_string(),
-                amount: Uint128::from(11u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
-    );
-}
-
-#[test]
-fn happy_days_withdraw_voting_tokens_all() {
-    let mut deps = mock_dependencies(&[]);
-    mock_instantiate(deps.as_mut());
-    mock_reply(deps.as_mut());
-
+    )
+    .unwrap();
     deps.querier.with_token_balances(&[(
         &VOTING_TOKEN.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(11u128))],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
     )]);
 
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
-    });
-
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_stake_tokens_result(11, execute_res);
-
-    let info = mock_info(TEST_VOTER, &[]);
-    let msg = ExecuteMsg::WithdrawVotingTokens { amount: None };
+    let msg = ExecuteMsg::CastVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Uint128::new(20),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
 
-    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let msg = execute_res.messages.get(0).expect("no message");
+    // the boosted votes are 50% of the supply, the staked amount that voted only 20%
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.yes_votes, Uint128::new(50));
 
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::EndPoll { poll_id: 1 };
+    let res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
     assert_eq!(
-        msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: VOTING_TOKEN.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: TEST_VOTER.to_string(),
-                amount: Uint128::from(11u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Quorum not reached"),
+            attr("passed", "false"),
+        ]
     );
 }
 
@@ -1769,9 +3197,16 @@ fn withdraw_voting_tokens() {
                 deposit_amount: Uint128::zero(),
                 link: None,
                 execute_data: None,
+                execute_msgs: vec![],
                 supply_snapshot: None,
                 required_quorum: Decimal::percent(DEFAULT_QUORUM),
                 required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+                poll_type: PollType::YesNo,
+                choices: vec![],
+                choice_votes: Uint128::zero(),
+                winning_choice: None,
+                participation: None,
+                deposit_held: false,
             },
         )
         .unwrap();
@@ -1792,9 +3227,16 @@ fn withdraw_voting_tokens() {
                 deposit_amount: Uint128::zero(),
                 link: None,
                 execute_data: None,
+                execute_msgs: vec![],
                 supply_snapshot: None,
                 required_quorum: Decimal::percent(DEFAULT_QUORUM),
                 required_threshold: Decimal::percent(DEFAULT_THRESHOLD),
+                poll_type: PollType::YesNo,
+                choices: vec![],
+                choice_votes: Uint128::zero(),
+                winning_choice: None,
+                participation: None,
+                deposit_held: false,
             },
         )
         .unwrap();
@@ -1839,6 +3281,7 @@ fn withdraw_voting_tokens() {
                         },
                     ),
                 ],
+                locks: vec![],
             },
         )
         .unwrap();
@@ -2130,6 +3573,7 @@ fn assert_cast_vote_success(
             attr("amount", amount.to_string()),
             attr("voter", voter),
             attr("vote_option", vote_option.to_string()),
+            attr("delegated_amount", "0"),
         ]
     );
 }
@@ -2152,6 +3596,8 @@ fn update_config() {
         snapshot_period: None,
         redemption_time: None,
         poll_gas_limit: None,
+        instant_redemption_fee: None,
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2174,7 +3620,9 @@ fn update_config() {
             proposal_deposit: Uint128::from(DEFAULT_PROPOSAL_DEPOSIT),
             snapshot_period: DEFAULT_SNAPSHOT_PERIOD,
             redemption_time: DEFAULT_REDEMPTION_TIME,
-            poll_gas_limit: DEFAULT_POLL_GAS_LIMIT
+            poll_gas_limit: DEFAULT_POLL_GAS_LIMIT,
+            instant_redemption_fee: None,
+            guardian: None,
         }
     );
 
@@ -2190,6 +3638,8 @@ fn update_config() {
         snapshot_period: Some(60u64),
         redemption_time: Some(1u64),
         poll_gas_limit: Some(2000000u64),
+        instant_redemption_fee: Some(Decimal::percent(5)),
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -2211,7 +3661,9 @@ fn update_config() {
             proposal_deposit: Uint128::from(123u128),
             snapshot_period: 60u64,
             redemption_time: 1u64,
-            poll_gas_limit: 2000000u64
+            poll_gas_limit: 2000000u64,
+            instant_redemption_fee: Some(Decimal::percent(5)),
+            guardian: None,
         }
     );
 
@@ -2227,6 +3679,8 @@ fn update_config() {
         snapshot_period: None,
         redemption_time: None,
         poll_gas_limit: None,
+        instant_redemption_fee: None,
+        guardian: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -2770,10 +4224,11 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
         "test".to_string(),
         "test".to_string(),
         None,
-        Some(PollExecuteMsg {
-            contract: VOTING_TOKEN.to_string(),
+        Some(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
-        }),
+            funds: vec![],
+        })]),
     );
 
     let execute_res = execute(
@@ -2830,6 +4285,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             attr("amount", "1000"),
             attr("voter", TEST_VOTER),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -2877,6 +4333,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             attr("amount", "1000"),
             attr("voter", TEST_VOTER_2),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -2920,10 +4377,11 @@ fn happy_days_end_poll_with_controlled_quorum() {
         "test".to_string(),
         "test".to_string(),
         None,
-        Some(PollExecuteMsg {
-            contract: VOTING_TOKEN.to_string(),
+        Some(vec![CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
             msg: exec_msg_bz,
-        }),
+            funds: vec![],
+        })]),
     );
 
     let execute_res = execute(
@@ -2980,6 +4438,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
             attr("amount", Uint128::from(stake_amount).to_string()),
             attr("voter", TEST_VOTER),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -3045,6 +4504,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
             attr("amount", Uint128::from(8 * stake_amount).to_string()),
             attr("voter", TEST_VOTER_2),
             attr("vote_option", "yes"),
+            attr("delegated_amount", "0"),
         ]
     );
 
@@ -3299,19 +4759,12 @@ fn redeem_xprism() {
             effective_underlying_prism: Uint128::from(500000u128 + 1000000u128),
             total_pending_withdraw_xprism: Uint128::from(500000u128),
             total_pending_withdraw_prism: Uint128::from(500000u128),
+            instant_redemption_fee: None,
         }
     );
 
-    // only one per block
-    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("can only execute one redeem_xprism operation per block")
-    );
-    // increase time
-    let mut env = mock_env();
-    env.block.time = env.block.time.plus_seconds(1);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    // redeems in the same block are merged in one order
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
         res.attributes,
         vec![
@@ -3321,6 +4774,170 @@ fn redeem_xprism() {
         ]
     );
     assert!(res.messages.is_empty());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::PrismWithdrawOrders {
+            address: TEST_VOTER.to_string(),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PrismWithdrawOrdersResponse = from_binary(&res).unwrap();
+    let end_time = mock_env()
+        .block
+        .time
+        .plus_seconds(DEFAULT_REDEMPTION_TIME)
+        .seconds();
+    assert_eq!(
+        response,
+        PrismWithdrawOrdersResponse {
+            claimable_amount: Uint128::zero(),
+            orders: vec![(end_time, Uint128::from(2000000u128))],
+        }
+    );
+
+    // claiming pays both redeems
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_REDEMPTION_TIME);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER, &[]),
+        ExecuteMsg::ClaimRedeemedXprism {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "claim_redeemed_prism"),
+            attr("prism_claimed", "2000000"),
+            attr("xprism_burned", "1000000"),
+        ]
+    );
+}
+
+#[test]
+fn instant_redeem_xprism() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_reply(deps.as_mut());
+
+    // 2:1 exchange rate
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(1000000u128))],
+        ),
+        (
+            &PRISM_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(2000000u128))],
+        ),
+    ]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(100000u128),
+        msg: to_binary(&Cw20HookMsg::InstantRedeemXprism {}).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("instant redemption is disabled"));
+
+    let update_msg = |instant_redemption_fee: Decimal| ExecuteMsg::UpdateConfig {
+        owner: None,
+        quorum: None,
+        threshold: None,
+        voting_period: None,
+        effective_delay: None,
+        proposal_deposit: None,
+        snapshot_period: None,
+        redemption_time: None,
+        poll_gas_limit: None,
+        instant_redemption_fee: Some(instant_redemption_fee),
+        guardian: None,
+    };
+    let creator_info = mock_info(TEST_CREATOR, &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator_info.clone(),
+        update_msg(Decimal::one()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("instant_redemption_fee must be smaller than 1")
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        creator_info,
+        update_msg(Decimal::percent(5)),
+    )
+    .unwrap();
+
+    // 200000 PRISM minus the 5% fee
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "instant_redeem_xprism"),
+            attr("xprism_burned", "100000"),
+            attr("prism_returned", "190000"),
+            attr("fee_amount", "10000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(100000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: PRISM_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_VOTER.to_string(),
+                    amount: Uint128::from(190000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    // the fee stays in the pool and raises the exchange rate
+    deps.querier.with_token_balances(&[
+        (
+            &VOTING_TOKEN.to_string(),
+            &[(&TEST_VOTER.to_string(), &Uint128::from(900000u128))],
+        ),
+        (
+            &PRISM_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1810000u128))],
+        ),
+    ]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::XprismState {}).unwrap();
+    let response: XprismStateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        XprismStateResponse {
+            exchange_rate: Decimal::from_ratio(1810000u128, 900000u128),
+            effective_xprism_supply: Uint128::from(900000u128),
+            effective_underlying_prism: Uint128::from(1810000u128),
+            total_pending_withdraw_xprism: Uint128::zero(),
+            total_pending_withdraw_prism: Uint128::zero(),
+            instant_redemption_fee: Some(Decimal::percent(5)),
+        }
+    );
 }
 
 #[test]
@@ -3712,6 +5329,7 @@ fn test_exchange_rate_after_claiming() {
             effective_underlying_prism: Uint128::from(100000u128),
             total_pending_withdraw_xprism: Uint128::zero(),
             total_pending_withdraw_prism: Uint128::zero(),
+            instant_redemption_fee: None,
         }
     );
 
@@ -3744,6 +5362,7 @@ fn test_exchange_rate_after_claiming() {
             effective_underlying_prism: Uint128::from(75000u128),
             total_pending_withdraw_xprism: Uint128::from(25000u128),
             total_pending_withdraw_prism: Uint128::from(25000u128),
+            instant_redemption_fee: None,
         }
     );
 
@@ -3775,6 +5394,7 @@ fn test_exchange_rate_after_claiming() {
             effective_underlying_prism: Uint128::from(85000u128),
             total_pending_withdraw_xprism: Uint128::from(25000u128),
             total_pending_withdraw_prism: Uint128::from(25000u128),
+            instant_redemption_fee: None,
         }
     );
 
@@ -3873,6 +5493,7 @@ fn test_exchange_rate_after_claiming() {
             effective_underlying_prism: Uint128::from(85000u128),
             total_pending_withdraw_xprism: Uint128::zero(),
             total_pending_withdraw_prism: Uint128::zero(),
+            instant_redemption_fee: None,
         }
     );
 }
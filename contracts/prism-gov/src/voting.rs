@@ -1,14 +1,23 @@
 use crate::state::{
-    bank_read, bank_store, config_store, poll_read, poll_voter_store, Config, Poll,
+    bank_read, bank_store, config_store, poll_read, poll_voter_store, Config, Poll, VotingLock,
     VotingTokenManager,
 };
 
 use cosmwasm_std::{
-    attr, to_binary, CanonicalAddr, CosmosMsg, Deps, DepsMut, MessageInfo, Response, StdError,
-    StdResult, Storage, Uint128, WasmMsg,
+    attr, to_binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
-use prism_protocol::gov::{PollStatus, VotingTokensResponse};
+use prism_protocol::gov::{
+    LockPositionResponse, LockPositionsResponse, PollStatus, VotingPowerResponse,
+    VotingTokensResponse,
+};
+
+pub const MIN_LOCK_DURATION: u64 = 7 * 24 * 60 * 60;
+pub const MAX_LOCK_DURATION: u64 = 4 * 365 * 24 * 60 * 60;
+/// Extra voting power of a lock of MAX_LOCK_DURATION, in percent of the locked amount
+pub const MAX_LOCK_BOOST_PERCENT: u64 = 150;
+pub const MAX_LOCKS_PER_USER: usize = 10;
 
 pub fn stake_voting_tokens(deps: DepsMut, sender: String, amount: Uint128) -> StdResult<Response> {
     if amount.is_zero() {
@@ -34,6 +43,7 @@ pub fn stake_voting_tokens(deps: DepsMut, sender: String, amount: Uint128) -> St
 // Withdraw amount if not staked. By default all funds will be withdrawn.
 pub fn withdraw_voting_tokens(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     amount: Option<Uint128>,
 ) -> StdResult<Response> {
@@ -48,6 +58,13 @@ pub fn withdraw_voting_tokens(
     let user_locked_balance =
         compute_locked_balance(deps.storage, &mut token_manager, &sender_address_raw)?;
 
+    // locked amounts can not be withdrawn until their lock expires
+    let current_seconds = env.block.time.seconds();
+    token_manager
+        .locks
+        .retain(|lock| lock.end_time > current_seconds);
+    let user_locked_balance = user_locked_balance.max(locked_amount(&token_manager));
+
     let withdrawable_balance = token_manager.deposit.checked_sub(user_locked_balance)?;
     let withdraw_amount = amount.unwrap_or(withdrawable_balance);
 
@@ -104,6 +121,116 @@ fn compute_locked_balance(
     Ok(Uint128::from(max_locked))
 }
 
+/*
+ * Locks staked tokens for a voting power multiplier
+ */
+pub fn lock_voting_tokens(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    duration: u64,
+) -> StdResult<Response> {
+    if !(MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&duration) {
+        return Err(StdError::generic_err(format!(
+            "Lock duration must be between {} and {} seconds",
+            MIN_LOCK_DURATION, MAX_LOCK_DURATION
+        )));
+    }
+    if amount.is_zero() {
+        return Err(StdError::generic_err(
+            "Lock amount must be greater than zero",
+        ));
+    }
+
+    let sender_address_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let key = sender_address_raw.as_slice();
+    let mut token_manager = bank_read(deps.storage)
+        .load(key)
+        .map_err(|_| StdError::generic_err("no voting information found for this address"))?;
+
+    let current_seconds = env.block.time.seconds();
+    token_manager
+        .locks
+        .retain(|lock| lock.end_time > current_seconds);
+
+    if locked_amount(&token_manager) + amount > token_manager.deposit {
+        return Err(StdError::generic_err(
+            "User does not have enough unlocked staked tokens",
+        ));
+    }
+    if token_manager.locks.len() >= MAX_LOCKS_PER_USER {
+        return Err(StdError::generic_err(format!(
+            "Can not have more than {} locks at the same time",
+            MAX_LOCKS_PER_USER
+        )));
+    }
+
+    let end_time = current_seconds + duration;
+    token_manager.locks.push(VotingLock {
+        amount,
+        start_time: current_seconds,
+        end_time,
+    });
+    bank_store(deps.storage).save(key, &token_manager)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "lock_voting_tokens"),
+        attr("sender", info.sender.as_str()),
+        attr("amount", amount.to_string()),
+        attr("end_time", end_time.to_string()),
+    ]))
+}
+
+// returns the staked amount in locks, expired locks should be removed first
+fn locked_amount(token_manager: &VotingTokenManager) -> Uint128 {
+    token_manager
+        .locks
+        .iter()
+        .fold(Uint128::zero(), |total, lock| total + lock.amount)
+}
+
+fn lock_multiplier(lock: &VotingLock, current_seconds: u64) -> Decimal {
+    let remaining = lock.end_time.saturating_sub(current_seconds);
+    Decimal::one()
+        + Decimal::from_ratio(
+            remaining as u128 * MAX_LOCK_BOOST_PERCENT as u128,
+            MAX_LOCK_DURATION as u128 * 100u128,
+        )
+}
+
+/// Staked balance plus the boost of the active locks, which decays linearly
+/// until their end time
+pub fn voting_power(token_manager: &VotingTokenManager, current_seconds: u64) -> Uint128 {
+    token_manager
+        .locks
+        .iter()
+        .filter(|lock| lock.end_time > current_seconds)
+        .fold(token_manager.deposit, |power, lock| {
+            power
+                + lock.amount.multiply_ratio(
+                    (lock.end_time - current_seconds) as u128 * MAX_LOCK_BOOST_PERCENT as u128,
+                    MAX_LOCK_DURATION as u128 * 100u128,
+                )
+        })
+}
+
+/// Voting power of `amount` of the staked balance, boosted by the average
+/// multiplier of the staked balance
+pub fn amount_voting_power(
+    token_manager: &VotingTokenManager,
+    amount: Uint128,
+    current_seconds: u64,
+) -> Uint128 {
+    if token_manager.deposit.is_zero() {
+        return amount;
+    }
+    amount.multiply_ratio(
+        voting_power(token_manager, current_seconds),
+        token_manager.deposit,
+    )
+}
+
 fn send_tokens(
     deps: DepsMut,
     asset_token: &CanonicalAddr,
@@ -154,3 +281,47 @@ pub fn query_voting_tokens(deps: Deps, address: String) -> StdResult<VotingToken
         locked_balance: token_manager.locked_balance,
     })
 }
+
+pub fn query_lock_positions(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<LockPositionsResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    let token_manager = bank_read(deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .unwrap_or_default();
+
+    let current_seconds = env.block.time.seconds();
+    let locks = token_manager
+        .locks
+        .iter()
+        .filter(|lock| lock.end_time > current_seconds)
+        .map(|lock| LockPositionResponse {
+            amount: lock.amount,
+            start_time: lock.start_time,
+            end_time: lock.end_time,
+            multiplier: lock_multiplier(lock, current_seconds),
+        })
+        .collect();
+
+    Ok(LockPositionsResponse { locks })
+}
+
+pub fn query_voting_power(deps: Deps, env: Env, address: String) -> StdResult<VotingPowerResponse> {
+    let addr_raw = deps.api.addr_canonicalize(&address)?;
+    let mut token_manager = bank_read(deps.storage)
+        .may_load(addr_raw.as_slice())?
+        .unwrap_or_default();
+
+    let current_seconds = env.block.time.seconds();
+    token_manager
+        .locks
+        .retain(|lock| lock.end_time > current_seconds);
+
+    Ok(VotingPowerResponse {
+        balance: token_manager.deposit,
+        locked_amount: locked_amount(&token_manager),
+        voting_power: voting_power(&token_manager, current_seconds),
+    })
+}
@@ -77,20 +77,14 @@ pub fn redeem_xprism(
 
     let end_time = env.block.time.plus_seconds(cfg.redemption_time).seconds();
 
-    // if a withdraw order exists for the same timestamp, its because another redeem tx was executed on the same block, return error
-    if WITHDRAW_ORDERS
-        .load(deps.storage, (sender.as_bytes(), &end_time.to_be_bytes()))
-        .is_ok()
-    {
-        return Err(StdError::generic_err(
-            "can only execute one redeem_xprism operation per block",
-        ));
-    };
-
-    WITHDRAW_ORDERS.save(
+    // redeems of the same sender in the same block end at the same time, so they are merged in one order
+    WITHDRAW_ORDERS.update(
         deps.storage,
         (sender.as_bytes(), &end_time.to_be_bytes()),
-        &(amount, prism_to_return),
+        |order| -> StdResult<(Uint128, Uint128)> {
+            let (xprism_amt, prism_amt) = order.unwrap_or_default();
+            Ok((xprism_amt + amount, prism_amt + prism_to_return))
+        },
     )?;
     TOTAL_PENDING_WITHDRAW.save(
         deps.storage,
@@ -104,6 +98,57 @@ pub fn redeem_xprism(
     ]))
 }
 
+/*
+ * Redeems xPRISM for PRISM right away, the instant redemption fee is kept
+ * in the pool for the remaining xPRISM holders
+ */
+pub fn instant_redeem_xprism(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let cfg = config_read(deps.storage).load()?;
+    let instant_redemption_fee = cfg
+        .instant_redemption_fee
+        .ok_or_else(|| StdError::generic_err("instant redemption is disabled"))?;
+    let prism_token = deps.api.addr_humanize(&cfg.prism_token)?;
+    let xprism_token = deps.api.addr_humanize(&cfg.xprism_token.unwrap())?;
+
+    let (pending_xprism, pending_prism) = TOTAL_PENDING_WITHDRAW.load(deps.storage)?;
+
+    let prism_amt =
+        query_token_balance(&deps.querier, &prism_token, &env.contract.address)? - pending_prism;
+    let xprism_amt = query_supply(&deps.querier, &xprism_token)? - pending_xprism;
+
+    let prism_redeemed = amount.multiply_ratio(prism_amt, xprism_amt);
+    let fee_amount = prism_redeemed * instant_redemption_fee;
+    let prism_to_return = prism_redeemed.checked_sub(fee_amount)?;
+
+    Ok(Response::new()
+        .add_messages(vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: xprism_token.into_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+                funds: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: prism_token.into_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: sender,
+                    amount: prism_to_return,
+                })?,
+                funds: vec![],
+            }),
+        ])
+        .add_attributes(vec![
+            attr("action", "instant_redeem_xprism"),
+            attr("xprism_burned", amount.to_string()),
+            attr("prism_returned", prism_to_return.to_string()),
+            attr("fee_amount", fee_amount.to_string()),
+        ]))
+}
+
 pub fn claim_redeemed_prism(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
     let cfg = config_read(deps.storage).load()?;
     let prism_token = deps.api.addr_humanize(&cfg.prism_token)?;
@@ -245,5 +290,6 @@ pub fn query_xprism_state(deps: Deps, env: Env) -> StdResult<XprismStateResponse
         effective_underlying_prism: prism_amt,
         total_pending_withdraw_xprism: pending_xprism,
         total_pending_withdraw_prism: pending_prism,
+        instant_redemption_fee: cfg.instant_redemption_fee,
     })
 }
@@ -3,8 +3,12 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use prism_protocol::vault::{
-    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, Cw20HookMsg, ExecuteMsg,
-    InstantiateMsg, QueryMsg, StateResponse, UnbondHistoryResponse, WhitelistedValidatorsResponse,
+    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, Cw20HookMsg, DelegationsResponse,
+    ExchangeRateHistoryResponse, ExecuteMsg, InstantiateMsg, NextBatchTimeResponse,
+    OperatorApprovalResponse, ParametersResponse, PauseStatusResponse, QueryMsg,
+    SimulateBondResponse, SimulateUnbondResponse, SimulateWithdrawResponse, SlashingEventsResponse,
+    StateResponse, UnbondClaimResponse, UnbondHistoryResponse, UserUnbondStatusResponse,
+    ValidatorStatusesResponse, ValidatorWeightsResponse, WhitelistedValidatorsResponse,
     WithdrawableUnbondedResponse,
 };
 
@@ -23,6 +27,20 @@ fn main() {
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
     export_schema(&schema_for!(UnbondHistoryResponse), &out_dir);
+    export_schema(&schema_for!(ValidatorWeightsResponse), &out_dir);
+    export_schema(&schema_for!(ValidatorStatusesResponse), &out_dir);
+    export_schema(&schema_for!(UnbondClaimResponse), &out_dir);
+    export_schema(&schema_for!(OperatorApprovalResponse), &out_dir);
+    export_schema(&schema_for!(ExchangeRateHistoryResponse), &out_dir);
+    export_schema(&schema_for!(SlashingEventsResponse), &out_dir);
+    export_schema(&schema_for!(DelegationsResponse), &out_dir);
+    export_schema(&schema_for!(PauseStatusResponse), &out_dir);
+    export_schema(&schema_for!(ParametersResponse), &out_dir);
     export_schema(&schema_for!(WhitelistedValidatorsResponse), &out_dir);
     export_schema(&schema_for!(WithdrawableUnbondedResponse), &out_dir);
+    export_schema(&schema_for!(SimulateBondResponse), &out_dir);
+    export_schema(&schema_for!(SimulateUnbondResponse), &out_dir);
+    export_schema(&schema_for!(SimulateWithdrawResponse), &out_dir);
+    export_schema(&schema_for!(NextBatchTimeResponse), &out_dir);
+    export_schema(&schema_for!(UserUnbondStatusResponse), &out_dir);
 }
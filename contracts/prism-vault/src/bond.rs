@@ -1,10 +1,12 @@
 use crate::contract::{query_total_issued, slashing};
+use crate::delegation::pick_bond_validators;
 use crate::math::decimal_division;
 use crate::state::{
-    is_valid_validator, read_valid_validators, CONFIG, CURRENT_BATCH, PARAMETERS, STATE,
+    add_delegation, assert_not_paused, is_valid_validator, Parameters, State, CONFIG,
+    CURRENT_BATCH, PARAMETERS, STATE,
 };
 use cosmwasm_std::{
-    attr, to_binary, Addr, Coin, CosmosMsg, DepsMut, Env, MessageInfo, QuerierWrapper, Response,
+    attr, to_binary, Addr, Attribute, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response,
     StakingMsg, StdError, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use cw0::must_pay;
@@ -17,7 +19,8 @@ pub fn execute_bond_split(
     validator: Option<String>,
 ) -> StdResult<Response> {
     let config = CONFIG.load(deps.storage)?.assert_initialized()?;
-    let (mint_amount_with_fee, mut sub_messages, payment_amt) =
+    assert_not_paused(deps.storage, |p| p.refraction, "refraction")?;
+    let (mint_amount_with_fee, mut sub_messages, payment_amt, extra_attrs) =
         _execute_bond(deps, &env, &info, &validator)?;
     // Pop last sub-message, which is the message to mint c-asset and send to the sender
     // Replace it with messages to mint c-asset for the contract, and mint p-asset and y-asset for the sender
@@ -56,16 +59,21 @@ pub fn execute_bond_split(
             attr("from", info.sender.as_str()),
             attr("bonded", payment_amt),
             attr("minted", mint_amount_with_fee),
-        ]))
+        ])
+        .add_attributes(extra_attrs))
 }
 
-/// Returns (mint_amount_with_fee, sub_messages, payment_amt).
+/// Returns (mint_amount_with_fee, sub_messages, payment_amt, extra_attributes),
+/// where mint_amount_with_fee is net of the peg recovery and mint fees and the
+/// extra attributes are the protocol fee and slashing attributes.
 pub fn _execute_bond(
     mut deps: DepsMut,
     env: &Env,
     info: &MessageInfo,
     validator: &Option<String>,
-) -> StdResult<(Uint128, Vec<SubMsg>, Uint128)> {
+) -> StdResult<(Uint128, Vec<SubMsg>, Uint128, Vec<Attribute>)> {
+    assert_not_paused(deps.storage, |p| p.minting, "minting")?;
+
     // validator must be whitelisted
     let selected_validator = match validator {
         Some(v) => {
@@ -77,17 +85,12 @@ pub fn _execute_bond(
                 ));
             }
 
-            validator_addr
-        }
-        None => {
-            let validators = read_valid_validators(deps.storage)?;
-            pick_bond_validator(&deps.querier, &env.contract.address, validators)?
+            Some(validator_addr)
         }
+        None => None,
     };
 
     let params = PARAMETERS.load(deps.storage)?;
-    let threshold = params.er_threshold;
-    let recovery_fee = params.peg_recovery_fee;
 
     // current batch requested fee is needed for accurate exchange rate computation.
     let current_batch = CURRENT_BATCH.load(deps.storage)?;
@@ -98,26 +101,34 @@ pub fn _execute_bond(
 
     // check slashing
     let mut state = STATE.load(deps.storage)?;
-    slashing(&mut deps, env.clone(), &mut state, &params)?;
+    let slashing_attrs = slashing(&mut deps, env.clone(), &mut state, &params)?;
+
+    // without a chosen validator the bond is split towards the target weights
+    let delegations = match selected_validator {
+        Some(validator_addr) => vec![(validator_addr, payment_amt)],
+        None => pick_bond_validators(deps.as_ref(), payment_amt)?,
+    };
 
     let sender = info.sender.clone();
 
     // get the total supply
     let mut total_supply = query_total_issued(deps.as_ref()).unwrap_or_default();
 
-    // peg recovery fee should be considered
-    let mint_amount = decimal_division(payment_amt, state.exchange_rate);
-    let mut mint_amount_with_fee = mint_amount;
-    if state.exchange_rate < threshold {
-        let max_peg_fee = mint_amount * recovery_fee;
-        let required_peg_fee = ((total_supply + mint_amount + current_batch.requested_with_fee)
-            .checked_sub(state.total_bond_amount + payment_amt))?;
-        let peg_fee = Uint128::min(max_peg_fee, required_peg_fee);
-        mint_amount_with_fee = (mint_amount.checked_sub(peg_fee))?;
-    }
+    let BondAmounts {
+        minted: mint_amount_with_fee,
+        protocol_fee,
+        ..
+    } = compute_bond_amounts(
+        &state,
+        &params,
+        total_supply,
+        requested_with_fee,
+        payment_amt,
+    )?;
 
     // total supply should be updated for exchange rate calculation.
-    total_supply += mint_amount_with_fee;
+    // The mint fee is minted as well, so it does not change the exchange rate.
+    total_supply += mint_amount_with_fee + protocol_fee;
 
     // exchange rate should be updated for future
     state.total_bond_amount += payment_amt;
@@ -125,27 +136,84 @@ pub fn _execute_bond(
     STATE.save(deps.storage, &state)?;
 
     let config = CONFIG.load(deps.storage)?.assert_initialized()?;
-    Ok((
-        mint_amount_with_fee,
-        vec![
-            SubMsg::new(CosmosMsg::Staking(StakingMsg::Delegate {
-                validator: selected_validator.to_string(),
-                amount: Coin {
-                    denom: params.underlying_coin_denom,
-                    amount: payment_amt,
-                },
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: config.cluna_contract.to_string(),
-                msg: to_binary(&TokenMsg::Mint {
-                    recipient: sender.to_string(),
-                    amount: mint_amount_with_fee,
-                })?,
-                funds: vec![],
-            })),
-        ],
-        payment_amt,
-    ))
+    let mut messages: Vec<SubMsg> = vec![];
+    for (validator, amount) in delegations {
+        add_delegation(deps.storage, &validator, amount)?;
+        messages.push(SubMsg::new(CosmosMsg::Staking(StakingMsg::Delegate {
+            validator: validator.to_string(),
+            amount: Coin {
+                denom: params.underlying_coin_denom.clone(),
+                amount,
+            },
+        })));
+    }
+
+    if !protocol_fee.is_zero() {
+        let collector = config
+            .collector
+            .ok_or_else(|| StdError::generic_err("Protocol fee collector is not set"))?;
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.cluna_contract.to_string(),
+            msg: to_binary(&TokenMsg::Mint {
+                recipient: collector.to_string(),
+                amount: protocol_fee,
+            })?,
+            funds: vec![],
+        })));
+    }
+
+    // the mint message must stay last, bond_split replaces it
+    messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.cluna_contract.to_string(),
+        msg: to_binary(&TokenMsg::Mint {
+            recipient: sender.to_string(),
+            amount: mint_amount_with_fee,
+        })?,
+        funds: vec![],
+    })));
+
+    let mut extra_attrs = vec![attr("protocol_fee", protocol_fee)];
+    extra_attrs.extend(slashing_attrs);
+
+    Ok((mint_amount_with_fee, messages, payment_amt, extra_attrs))
+}
+
+/// cLuna amounts of a bond
+pub(crate) struct BondAmounts {
+    // minted for the sender
+    pub minted: Uint128,
+    pub peg_fee: Uint128,
+    // minted for the collector
+    pub protocol_fee: Uint128,
+}
+
+/// Splits the cLuna minted for `payment_amt` into the sender's share and the
+/// fees, given the state after the slashing check.
+/// Also used by the SimulateBond query.
+pub(crate) fn compute_bond_amounts(
+    state: &State,
+    params: &Parameters,
+    total_supply: Uint128,
+    requested_with_fee: Uint128,
+    payment_amt: Uint128,
+) -> StdResult<BondAmounts> {
+    // peg recovery fee should be considered
+    let mint_amount = decimal_division(payment_amt, state.exchange_rate);
+    let mut peg_fee = Uint128::zero();
+    if state.exchange_rate < params.er_threshold {
+        let max_peg_fee = mint_amount * params.peg_recovery_fee;
+        let required_peg_fee = ((total_supply + mint_amount + requested_with_fee)
+            .checked_sub(state.total_bond_amount + payment_amt))?;
+        peg_fee = Uint128::min(max_peg_fee, required_peg_fee);
+    }
+    let mint_amount_with_fee = (mint_amount.checked_sub(peg_fee))?;
+
+    let protocol_fee = mint_amount_with_fee * params.mint_fee;
+    Ok(BondAmounts {
+        minted: mint_amount_with_fee.checked_sub(protocol_fee)?,
+        peg_fee,
+        protocol_fee,
+    })
 }
 
 pub fn execute_bond(
@@ -154,7 +222,7 @@ pub fn execute_bond(
     info: MessageInfo,
     validator: Option<String>,
 ) -> StdResult<Response> {
-    let (mint_amount_with_fee, messages, payment_amt) =
+    let (mint_amount_with_fee, messages, payment_amt, extra_attrs) =
         _execute_bond(deps, &env, &info, &validator)?;
     Ok(Response::new()
         .add_submessages(messages)
@@ -163,37 +231,6 @@ pub fn execute_bond(
             attr("from", info.sender.as_str()),
             attr("bonded", payment_amt),
             attr("minted", mint_amount_with_fee),
-        ]))
-}
-
-fn pick_bond_validator(
-    querier: &QuerierWrapper,
-    contract_addr: &Addr,
-    validators: Vec<Addr>,
-) -> StdResult<Addr> {
-    if validators.is_empty() {
-        return Err(StdError::generic_err(
-            "There are not validators to pick from",
-        ));
-    }
-
-    let mut delegations = vec![];
-    for val in validators {
-        if querier.query_validator(&val)?.is_none() {
-            continue;
-        }
-
-        let delegation = querier.query_delegation(contract_addr, &val)?;
-        if delegation.is_none() {
-            return Ok(val);
-        }
-
-        delegations.push((delegation.unwrap().amount.amount.u128(), val));
-    }
-
-    if delegations.is_empty() {
-        return Err(StdError::generic_err("All validators are jailed"));
-    }
-    delegations.sort();
-    Ok(delegations.first().unwrap().1.clone())
+        ])
+        .add_attributes(extra_attrs))
 }
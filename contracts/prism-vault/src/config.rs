@@ -1,8 +1,9 @@
 use crate::{
     contract::validate_rate,
     state::{
-        is_valid_validator, read_validators, remove_white_validators, store_white_validators,
-        Parameters, CONFIG, PARAMETERS,
+        is_valid_validator, move_delegation, read_pause_status, read_validators,
+        remove_white_validators, store_validator_weight, store_white_validators, Parameters,
+        CONFIG, PARAMETERS, PAUSE_STATUS, VALIDATOR_STATUS,
     },
 };
 use cosmwasm_std::{
@@ -11,8 +12,12 @@ use cosmwasm_std::{
     WasmMsg,
 };
 use cw20::MinterResponse;
-use prism_protocol::{internal::parse_reply_instantiate_data, vault::ExecuteMsg};
+use prism_protocol::{
+    internal::parse_reply_instantiate_data,
+    vault::{ExecuteMsg, ValidatorWeight, MAX_PROTOCOL_FEE},
+};
 use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
+use std::str::FromStr;
 
 pub const MAX_VALIDATORS: u64 = 20;
 
@@ -24,24 +29,24 @@ pub fn set_token_address(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Respo
     let token_addr = deps.api.addr_validate(&res.contract_address)?;
 
     let mut attributes: Vec<Attribute> = vec![];
-    let (next_reply_id, next_token_name, next_token_symbol) = match msg.id {
+    let (next_reply_id, next_token_prefix) = match msg.id {
         0 => {
             attributes.push(attr("cluna_address", token_addr.as_str()));
             config.cluna_contract = token_addr;
 
-            (1u64, "Prism pLuna Token", "pLuna")
+            (1u64, "p")
         }
         1 => {
             attributes.push(attr("pluna_address", token_addr.as_str()));
             config.pluna_contract = token_addr;
 
-            (2u64, "Prism yLuna Token", "yLuna")
+            (2u64, "y")
         }
         2 => {
             attributes.push(attr("yluna_address", token_addr.as_str()));
             config.yluna_contract = token_addr;
 
-            (3u64, "", "")
+            (3u64, "")
         }
         _ => return Err(StdError::generic_err("invalid reply id")),
     };
@@ -54,8 +59,11 @@ pub fn set_token_address(deps: DepsMut, env: Env, msg: Reply) -> StdResult<Respo
             msg: WasmMsg::Instantiate {
                 code_id: config.token_code_id,
                 msg: to_binary(&TokenInstantiateMsg {
-                    name: next_token_name.to_string(),
-                    symbol: next_token_symbol.to_string(),
+                    name: format!(
+                        "Prism {}{} Token",
+                        next_token_prefix, config.underlying_symbol
+                    ),
+                    symbol: format!("{}{}", next_token_prefix, config.underlying_symbol),
                     decimals: 6,
                     initial_balances: vec![],
                     mint: Some(MinterResponse {
@@ -90,6 +98,11 @@ pub fn execute_update_params(
     unbonding_period: Option<u64>,
     peg_recovery_fee: Option<Decimal>,
     er_threshold: Option<Decimal>,
+    rebalance_cap: Option<Decimal>,
+    commission_ceiling: Option<Decimal>,
+    mint_fee: Option<Decimal>,
+    unbond_fee: Option<Decimal>,
+    keeper_fee: Option<Decimal>,
 ) -> StdResult<Response> {
     // only owner can send this message.
     let config = CONFIG.load(deps.storage)?;
@@ -106,16 +119,40 @@ pub fn execute_update_params(
         unbonding_period: unbonding_period.unwrap_or(params.unbonding_period),
         peg_recovery_fee: validate_rate(peg_recovery_fee.unwrap_or(params.peg_recovery_fee))?,
         er_threshold: validate_rate(er_threshold.unwrap_or(params.er_threshold))?,
+        rebalance_cap: validate_rate(rebalance_cap.unwrap_or(params.rebalance_cap))?,
+        commission_ceiling: validate_rate(commission_ceiling.unwrap_or(params.commission_ceiling))?,
+        mint_fee: validate_protocol_fee(mint_fee.unwrap_or(params.mint_fee))?,
+        unbond_fee: validate_protocol_fee(unbond_fee.unwrap_or(params.unbond_fee))?,
+        keeper_fee: validate_protocol_fee(keeper_fee.unwrap_or(params.keeper_fee))?,
     };
 
+    // fees can only be taken once there is a collector to send them to
+    if config.collector.is_none()
+        && !(new_params.mint_fee.is_zero() && new_params.unbond_fee.is_zero())
+    {
+        return Err(StdError::generic_err("Protocol fee collector is not set"));
+    }
+
     PARAMETERS.save(deps.storage, &new_params)?;
 
     Ok(Response::new().add_attributes(vec![attr("action", "update_params")]))
 }
 
+fn validate_protocol_fee(fee: Decimal) -> StdResult<Decimal> {
+    if fee > Decimal::from_str(MAX_PROTOCOL_FEE)? {
+        return Err(StdError::generic_err(format!(
+            "fee can not be greater than {}",
+            MAX_PROTOCOL_FEE
+        )));
+    }
+
+    Ok(fee)
+}
+
 /// Update the config. Update the owner, reward and airdrop contract
 /// Also used to post initialize the contract
 /// Only creator/owner is allowed to execute
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -123,6 +160,9 @@ pub fn execute_update_config(
     yluna_staking: Option<String>,
     airdrop_registry_contract: Option<String>,
     manager: Option<String>,
+    prismswap_router: Option<String>,
+    guardian: Option<String>,
+    collector: Option<String>,
 ) -> StdResult<Response> {
     // only owner must be able to send this message.
     let mut config = CONFIG.load(deps.storage)?;
@@ -155,6 +195,18 @@ pub fn execute_update_config(
         config.manager = deps.api.addr_validate(&manager)?;
     }
 
+    if let Some(prismswap_router) = prismswap_router {
+        config.prismswap_router = Some(deps.api.addr_validate(&prismswap_router)?);
+    }
+
+    if let Some(guardian) = guardian {
+        config.guardian = Some(deps.api.addr_validate(&guardian)?);
+    }
+
+    if let Some(collector) = collector {
+        config.collector = Some(deps.api.addr_validate(&collector)?);
+    }
+
     let placeholder_addr = Addr::unchecked("");
     if !config.initialized
         && config.yluna_staking.ne(&placeholder_addr)
@@ -170,6 +222,82 @@ pub fn execute_update_config(
         .add_attributes(vec![attr("action", "update_config")]))
 }
 
+pub fn execute_remove_prismswap_router(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    config.prismswap_router = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "remove_prismswap_router")]))
+}
+
+pub fn execute_remove_guardian(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    config.guardian = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "remove_guardian")]))
+}
+
+/// The protocol fees must be set to zero first, they could not be sent
+/// anywhere otherwise
+pub fn execute_remove_collector(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let params = PARAMETERS.load(deps.storage)?;
+    if !(params.mint_fee.is_zero() && params.unbond_fee.is_zero()) {
+        return Err(StdError::generic_err(
+            "Protocol fees must be zero to remove the collector",
+        ));
+    }
+
+    config.collector = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![attr("action", "remove_collector")]))
+}
+
+/// Pause or unpause categories of operations.
+/// Only the guardian or the owner is allowed to execute
+pub fn execute_update_pause_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    minting: Option<bool>,
+    refraction: Option<bool>,
+    unbonding: Option<bool>,
+    withdrawals: Option<bool>,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner && Some(&info.sender) != config.guardian.as_ref() {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut status = read_pause_status(deps.storage)?;
+    status.minting = minting.unwrap_or(status.minting);
+    status.refraction = refraction.unwrap_or(status.refraction);
+    status.unbonding = unbonding.unwrap_or(status.unbonding);
+    status.withdrawals = withdrawals.unwrap_or(status.withdrawals);
+    PAUSE_STATUS.save(deps.storage, &status)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_pause_status"),
+        attr("minting", status.minting.to_string()),
+        attr("refraction", status.refraction.to_string()),
+        attr("unbonding", status.unbonding.to_string()),
+        attr("withdrawals", status.withdrawals.to_string()),
+    ]))
+}
+
 /// Register a white listed validator.
 /// Only creator/owner is allowed to execute
 pub fn execute_register_validator(
@@ -206,6 +334,7 @@ pub fn execute_register_validator(
 
     let validator_addr = Addr::unchecked(&validator);
     store_white_validators(deps.storage, &validator_addr)?;
+    VALIDATOR_STATUS.remove(deps.storage, &validator_addr);
 
     Ok(Response::new().add_attributes(vec![
         attr("action", "register_validator"),
@@ -213,6 +342,37 @@ pub fn execute_register_validator(
     ]))
 }
 
+/// Update the target delegation weights of whitelisted validators.
+/// Weights are relative to each other, new bonds are split and unbonds are
+/// taken so that delegations move towards these targets.
+/// Only creator/owner is allowed to execute
+pub fn execute_update_validator_weights(
+    deps: DepsMut,
+    info: MessageInfo,
+    weights: Vec<ValidatorWeight>,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let mut attributes: Vec<Attribute> = vec![attr("action", "update_validator_weights")];
+    for item in weights {
+        let validator_addr = Addr::unchecked(&item.validator);
+        if !is_valid_validator(deps.storage, &validator_addr)? {
+            return Err(StdError::generic_err(format!(
+                "Validator {} is not whitelisted",
+                item.validator
+            )));
+        }
+
+        store_validator_weight(deps.storage, &validator_addr, item.weight)?;
+        attributes.push(attr(item.validator, item.weight.to_string()));
+    }
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
 /// Deregister a previously-whitelisted validator.
 /// Only creator/owner is allowed to execute
 pub fn execute_deregister_validator(
@@ -256,6 +416,12 @@ pub fn execute_deregister_validator(
         let delegated_amount = q;
 
         if let Some(delegation) = delegated_amount {
+            move_delegation(
+                deps.storage,
+                &validator_addr,
+                &redel_validator,
+                delegation.amount.amount,
+            )?;
             messages.push(SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
                 src_validator: validator.to_string(),
                 dst_validator: redel_validator.to_string(),
@@ -333,6 +499,8 @@ pub fn execute_redelegate(
         ));
     };
 
+    move_delegation(deps.storage, &source_val_addr, &target_val_addr, amount)?;
+
     let messages: Vec<SubMsg> = vec![
         SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
             src_validator: source_val_addr.to_string(),
@@ -1,7 +1,7 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    attr, from_binary, to_binary, Addr, Attribute, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
     DistributionMsg, Env, MessageInfo, QueryRequest, Reply, ReplyOn, Response, StakingMsg,
     StdError, StdResult, SubMsg, Uint128, WasmMsg, WasmQuery,
 };
@@ -9,26 +9,42 @@ use cw2::set_contract_version;
 
 use crate::config::{
     execute_deregister_validator, execute_redelegate, execute_register_validator,
-    execute_update_config, execute_update_params, set_token_address,
+    execute_remove_collector, execute_remove_guardian, execute_remove_prismswap_router,
+    execute_update_config, execute_update_params, execute_update_pause_status,
+    execute_update_validator_weights, set_token_address,
 };
 
 use crate::state::{
-    all_unbond_history, get_unbond_requests, query_get_finished_amount, read_valid_validators,
-    Config, CurrentBatch, Parameters, State, CONFIG, CURRENT_BATCH, PARAMETERS, STATE,
+    add_delegation, all_unbond_history, default_rebalance_cap, default_underlying_symbol,
+    get_unbond_requests, query_get_finished_amount, read_delegations, read_exchange_rate_history,
+    read_pause_status, read_slashing_events, read_unbond_history, read_unbond_wait_list,
+    read_valid_validators, read_validator_statuses, read_validator_weight,
+    store_exchange_rate_snapshot, store_slashing_event, Config, CurrentBatch, Parameters,
+    SlashingEvent, State, CONFIG, CURRENT_BATCH, DELEGATIONS, OPERATORS, PARAMETERS, STATE,
+};
+use crate::unbond::{
+    compute_unbond_amounts, compute_withdraw_rates, execute_approve_operator,
+    execute_cancel_unbond, execute_instant_unbond, execute_process_batch, execute_revoke_operator,
+    execute_transfer_unbond_claim, execute_unbond, execute_withdraw_unbonded, expected_batch_time,
+    UnbondAmounts,
 };
-use crate::unbond::{execute_unbond, execute_withdraw_unbonded};
 
-use crate::bond::{execute_bond, execute_bond_split};
-use crate::refract::{merge, split};
+use crate::bond::{compute_bond_amounts, execute_bond, execute_bond_split, BondAmounts};
+use crate::delegation::{execute_check_validators, execute_rebalance};
+use crate::refract::{merge, split, split_received};
 use cw0::must_pay;
 use cw20::{
     Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse,
 };
 use cw_asset::{Asset, AssetInfo};
 use prism_protocol::vault::{
-    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, Cw20HookMsg, ExecuteMsg,
-    InstantiateMsg, QueryMsg, StateResponse, UnbondRequestsResponse, WhitelistedValidatorsResponse,
-    WithdrawableUnbondedResponse,
+    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, Cw20HookMsg, DelegationResponse,
+    DelegationsResponse, ExchangeRateHistoryResponse, ExecuteMsg, InstantiateMsg,
+    NextBatchTimeResponse, OperatorApprovalResponse, ParametersResponse, QueryMsg,
+    SimulateBondResponse, SimulateUnbondResponse, SimulateWithdrawResponse, SlashingEventsResponse,
+    StateResponse, UnbondClaimResponse, UnbondRequestStatusResponse, UnbondRequestsResponse,
+    UserUnbondStatusResponse, ValidatorStatusesResponse, ValidatorWeightResponse,
+    ValidatorWeightsResponse, WhitelistedValidatorsResponse, WithdrawableUnbondedResponse,
 };
 use prism_protocol::yasset_staking::ExecuteMsg as StakingExecuteMsg;
 use prismswap::querier::query_token_balance;
@@ -48,7 +64,7 @@ pub fn instantiate(
 
     let sender = info.sender.clone();
 
-    // Find Luna amount that was sent as part of this instantiate message. This
+    // Find the underlying amount that was sent as part of this instantiate message. This
     // amount will be immediately delegated to a validator.
     let payment_amt = must_pay(&info, &msg.underlying_coin_denom)
         .map_err(|error| StdError::generic_err(format!("{}", error)))?;
@@ -66,6 +82,12 @@ pub fn instantiate(
         token_code_id: msg.token_code_id,
         token_admin: deps.api.addr_validate(&msg.token_admin)?,
         manager: deps.api.addr_validate(&msg.manager)?,
+        prismswap_router: None,
+        guardian: None,
+        underlying_symbol: msg
+            .underlying_symbol
+            .unwrap_or_else(default_underlying_symbol),
+        collector: None,
     };
     CONFIG.save(deps.storage, &config)?;
 
@@ -87,12 +109,18 @@ pub fn instantiate(
         unbonding_period: msg.unbonding_period,
         peg_recovery_fee: validate_rate(msg.peg_recovery_fee)?,
         er_threshold: validate_rate(msg.er_threshold)?,
+        rebalance_cap: validate_rate(msg.rebalance_cap.unwrap_or_else(default_rebalance_cap))?,
+        commission_ceiling: Decimal::one(),
+        mint_fee: Decimal::zero(),
+        unbond_fee: Decimal::zero(),
+        keeper_fee: Decimal::zero(),
     };
     PARAMETERS.save(deps.storage, &params)?;
 
     let batch = CurrentBatch {
         id: 1,
         requested_with_fee: Default::default(),
+        keeper_reward: Default::default(),
     };
     CURRENT_BATCH.save(deps.storage, &batch)?;
 
@@ -109,6 +137,11 @@ pub fn instantiate(
     })));
 
     // send the delegate message
+    add_delegation(
+        deps.storage,
+        &deps.api.addr_validate(&msg.validator)?,
+        payment_amt,
+    )?;
     messages.push(SubMsg::new(CosmosMsg::Staking(StakingMsg::Delegate {
         validator: msg.validator.to_string(),
         amount: Coin {
@@ -122,8 +155,8 @@ pub fn instantiate(
         msg: WasmMsg::Instantiate {
             code_id: config.token_code_id,
             msg: to_binary(&TokenInstantiateMsg {
-                name: "Prism cLuna Token".to_string(),
-                symbol: "cLuna".to_string(),
+                name: format!("Prism c{} Token", config.underlying_symbol),
+                symbol: format!("c{}", config.underlying_symbol),
                 decimals: 6,
                 initial_balances: vec![
                     (Cw20Coin {
@@ -161,11 +194,25 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::Bond { validator } => execute_bond(deps, env, info, validator), // Bond luna to c-luna
         ExecuteMsg::BondSplit { validator } => execute_bond_split(deps, env, info, validator), // Bond luna to p-luna/y-luna
         ExecuteMsg::Split { amount } => split(deps, env, info, amount), // Split c-luna to p-luna/y-luna
-        ExecuteMsg::Merge { amount } => merge(deps, info, amount), // Merge p-luna/y-luna to c-luna
+        ExecuteMsg::Merge { amount, recipient } => merge(deps, info, amount, recipient), // Merge p-luna/y-luna to c-luna
         ExecuteMsg::UpdateGlobalIndex { airdrop_hooks } => {
             execute_update_global(deps, env, airdrop_hooks)
         }
-        ExecuteMsg::WithdrawUnbonded {} => execute_withdraw_unbonded(deps, env, info),
+        ExecuteMsg::WithdrawUnbonded { owner, recipient } => {
+            execute_withdraw_unbonded(deps, env, info, owner, recipient)
+        }
+        ExecuteMsg::ApproveOperator {
+            operator,
+            recipient,
+        } => execute_approve_operator(deps, info, operator, recipient),
+        ExecuteMsg::RevokeOperator { operator } => execute_revoke_operator(deps, info, operator),
+        ExecuteMsg::CancelUnbond { batch_id, amount } => {
+            execute_cancel_unbond(deps, info, batch_id, amount)
+        }
+        ExecuteMsg::TransferUnbondClaim {
+            batch_id,
+            recipient,
+        } => execute_transfer_unbond_claim(deps, info, batch_id, recipient),
         ExecuteMsg::RegisterValidator { validator } => {
             execute_register_validator(deps, env, info, validator)
         }
@@ -173,12 +220,20 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             validator,
             redel_validator,
         } => execute_deregister_validator(deps, env, info, validator, redel_validator),
+        ExecuteMsg::ProcessBatch {} => execute_process_batch(deps, env, info),
         ExecuteMsg::CheckSlashing {} => execute_slashing(deps, env),
+        ExecuteMsg::CheckValidators {} => execute_check_validators(deps, env),
+        ExecuteMsg::Rebalance { max_moves } => execute_rebalance(deps, env, max_moves),
         ExecuteMsg::UpdateParams {
             epoch_period,
             unbonding_period,
             peg_recovery_fee,
             er_threshold,
+            rebalance_cap,
+            commission_ceiling,
+            mint_fee,
+            unbond_fee,
+            keeper_fee,
         } => execute_update_params(
             deps,
             env,
@@ -187,12 +242,23 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             unbonding_period,
             peg_recovery_fee,
             er_threshold,
+            rebalance_cap,
+            commission_ceiling,
+            mint_fee,
+            unbond_fee,
+            keeper_fee,
         ),
+        ExecuteMsg::UpdateValidatorWeights { weights } => {
+            execute_update_validator_weights(deps, info, weights)
+        }
         ExecuteMsg::UpdateConfig {
             owner,
             yluna_staking,
             airdrop_registry_contract,
             manager,
+            prismswap_router,
+            guardian,
+            collector,
         } => execute_update_config(
             deps,
             info,
@@ -200,7 +266,19 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             yluna_staking,
             airdrop_registry_contract,
             manager,
+            prismswap_router,
+            guardian,
+            collector,
         ),
+        ExecuteMsg::RemovePrismswapRouter {} => execute_remove_prismswap_router(deps, info),
+        ExecuteMsg::RemoveGuardian {} => execute_remove_guardian(deps, info),
+        ExecuteMsg::RemoveCollector {} => execute_remove_collector(deps, info),
+        ExecuteMsg::UpdatePauseStatus {
+            minting,
+            refraction,
+            unbonding,
+            withdrawals,
+        } => execute_update_pause_status(deps, info, minting, refraction, unbonding, withdrawals),
         ExecuteMsg::ClaimAirdrop {
             airdrop_token_contract,
             airdrop_contract,
@@ -240,6 +318,22 @@ pub fn receive_cw20(
             }
             execute_unbond(deps, env, cw20_msg.amount, cw20_msg.sender)
         }
+        Ok(Cw20HookMsg::InstantUnbond { min_receive }) => {
+            // only cLuna token contract can execute this message
+            let conf = CONFIG.load(deps.storage)?.assert_initialized()?;
+            if info.sender != conf.cluna_contract {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+            execute_instant_unbond(deps, cw20_msg.amount, cw20_msg.sender, min_receive)
+        }
+        Ok(Cw20HookMsg::Split { recipient }) => {
+            // only cLuna token contract can execute this message
+            let conf = CONFIG.load(deps.storage)?.assert_initialized()?;
+            if info.sender != conf.cluna_contract {
+                return Err(StdError::generic_err("unauthorized"));
+            }
+            split_received(deps, cw20_msg.amount, cw20_msg.sender, recipient)
+        }
         Err(err) => Err(err),
     }
 }
@@ -285,10 +379,11 @@ pub fn execute_update_global(
     })));
 
     // update state last modified
-    STATE.update(deps.storage, |mut last_state| -> StdResult<State> {
+    let state = STATE.update(deps.storage, |mut last_state| -> StdResult<State> {
         last_state.last_index_modification = env.block.time.seconds();
         Ok(last_state)
     })?;
+    store_exchange_rate_snapshot(deps.storage, env.block.time.seconds(), &state)?;
 
     Ok(Response::new()
         .add_submessages(messages)
@@ -315,25 +410,50 @@ fn withdraw_all_rewards(deps: &DepsMut, delegator: Addr) -> StdResult<Vec<SubMsg
 
 /// Check whether slashing has happened
 /// This is used for checking slashing while bonding or unbonding
+/// Returns the attributes of the slashing events found per validator
 pub fn slashing(
     deps: &mut DepsMut,
     env: Env,
     state: &mut State,
     params: &Parameters,
-) -> StdResult<()> {
+) -> StdResult<Vec<Attribute>> {
     // Check the actual bonded amount
-    let delegations = deps.querier.query_all_delegations(env.contract.address)?;
+    let delegations = deps.querier.query_all_delegations(&env.contract.address)?;
     if delegations.is_empty() {
-        Ok(())
+        Ok(vec![])
     } else {
         let mut actual_total_bonded = Uint128::zero();
+        let mut slashed: Vec<(Addr, Uint128, Uint128)> = vec![];
+
+        // expected delegations without a live one are stale, e.g. zero entries or
+        // delegations removed outside the tracked paths, and dropped unreported
+        for (validator, _) in read_delegations(deps.storage)? {
+            if !delegations
+                .iter()
+                .any(|d| d.validator == validator.as_str())
+            {
+                DELEGATIONS.remove(deps.storage, &validator);
+            }
+        }
+
         for delegation in delegations {
             if delegation.amount.denom == params.underlying_coin_denom {
-                actual_total_bonded += delegation.amount.amount
+                let actual = delegation.amount.amount;
+                actual_total_bonded += actual;
+
+                // compare with the expected delegation, then resync it
+                let validator = Addr::unchecked(delegation.validator);
+                if let Some(expected) = DELEGATIONS.may_load(deps.storage, &validator)? {
+                    if expected > actual {
+                        slashed.push((validator.clone(), expected, actual));
+                    }
+                }
+                DELEGATIONS.save(deps.storage, &validator, &actual)?;
             }
         }
 
         // Slashing happens if the expected amount is less than stored amount
+        let rate_before = state.exchange_rate;
         if state.total_bond_amount > actual_total_bonded {
             // Need total issued for updating the exchange rate
             let total_issued = query_total_issued(deps.as_ref())?;
@@ -341,12 +461,58 @@ pub fn slashing(
             state.total_bond_amount = actual_total_bonded;
             state.update_exchange_rate(total_issued, current_requested_fee);
             STATE.save(deps.storage, state)?;
+            store_exchange_rate_snapshot(deps.storage, env.block.time.seconds(), state)?;
+        }
+
+        let mut attributes: Vec<Attribute> = vec![];
+        for (validator, expected_delegation, actual_delegation) in slashed {
+            let id = store_slashing_event(
+                deps.storage,
+                &SlashingEvent {
+                    validator: validator.clone(),
+                    expected_delegation,
+                    actual_delegation,
+                    time: env.block.time.seconds(),
+                    rate_before,
+                    rate_after: state.exchange_rate,
+                },
+            )?;
+            attributes.push(attr("slashing_event", id.to_string()));
+            attributes.push(attr("slashed_validator", validator));
+            attributes.push(attr("expected_delegation", expected_delegation));
+            attributes.push(attr("actual_delegation", actual_delegation));
         }
 
-        Ok(())
+        Ok(attributes)
     }
 }
 
+/// Applies the exchange rate change of `slashing` to the state without
+/// storing anything, for the simulation queries.
+pub(crate) fn simulate_slashing(
+    deps: Deps,
+    env: &Env,
+    state: &mut State,
+    params: &Parameters,
+) -> StdResult<()> {
+    let delegations = deps.querier.query_all_delegations(&env.contract.address)?;
+    if delegations.is_empty() {
+        return Ok(());
+    }
+    let actual_total_bonded: Uint128 = delegations
+        .into_iter()
+        .filter(|d| d.amount.denom == params.underlying_coin_denom)
+        .map(|d| d.amount.amount)
+        .sum();
+    if state.total_bond_amount > actual_total_bonded {
+        let total_issued = query_total_issued(deps)?;
+        let current_requested_fee = CURRENT_BATCH.load(deps.storage)?.requested_with_fee;
+        state.total_bond_amount = actual_total_bonded;
+        state.update_exchange_rate(total_issued, current_requested_fee);
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn claim_airdrop(
     deps: DepsMut,
@@ -432,12 +598,14 @@ pub fn execute_slashing(mut deps: DepsMut, env: Env) -> StdResult<Response> {
     // call slashing
     let params = PARAMETERS.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
-    slashing(&mut deps, env, &mut state, &params)?;
+    let slashing_attrs = slashing(&mut deps, env, &mut state, &params)?;
     // read state for log
-    Ok(Response::new().add_attributes(vec![
-        attr("action", "check_slashing"),
-        attr("new_exchange_rate", state.exchange_rate.to_string()),
-    ]))
+    Ok(Response::new()
+        .add_attributes(vec![
+            attr("action", "check_slashing"),
+            attr("new_exchange_rate", state.exchange_rate.to_string()),
+        ])
+        .add_attributes(slashing_attrs))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -456,9 +624,53 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_from,
             limit,
         } => to_binary(&query_unbond_requests(deps, address, start_from, limit)?),
-        QueryMsg::AllHistory { start_from, limit } => {
-            to_binary(&query_unbond_requests_limitation(deps, start_from, limit)?)
+        QueryMsg::OperatorApproval { owner, operator } => {
+            to_binary(&query_operator_approval(deps, owner, operator)?)
         }
+        QueryMsg::UnbondClaim { address, batch_id } => {
+            to_binary(&query_unbond_claim(deps, address, batch_id)?)
+        }
+        QueryMsg::AllHistory {
+            start_from,
+            limit,
+            released,
+        } => to_binary(&query_unbond_requests_limitation(
+            deps, start_from, limit, released,
+        )?),
+        QueryMsg::UserUnbondStatus {
+            address,
+            start_from,
+            limit,
+        } => to_binary(&query_user_unbond_status(
+            deps, env, address, start_from, limit,
+        )?),
+        QueryMsg::ValidatorWeights {} => to_binary(&query_validator_weights(deps, env)?),
+        QueryMsg::ExchangeRateHistory {
+            start_after,
+            end_before,
+            limit,
+        } => to_binary(&query_exchange_rate_history(
+            deps,
+            start_after,
+            end_before,
+            limit,
+        )?),
+        QueryMsg::SlashingEvents { start_after, limit } => {
+            to_binary(&query_slashing_events(deps, start_after, limit)?)
+        }
+        QueryMsg::ValidatorStatuses { start_after, limit } => {
+            to_binary(&query_validator_statuses(deps, start_after, limit)?)
+        }
+        QueryMsg::Delegations {} => to_binary(&query_delegations(deps, env)?),
+        QueryMsg::PauseStatus {} => to_binary(&read_pause_status(deps.storage)?.as_res()),
+        QueryMsg::SimulateBond { amount } => to_binary(&query_simulate_bond(deps, env, amount)?),
+        QueryMsg::SimulateUnbond { amount } => {
+            to_binary(&query_simulate_unbond(deps, env, amount)?)
+        }
+        QueryMsg::SimulateWithdraw { address } => {
+            to_binary(&query_simulate_withdraw(deps, env, address)?)
+        }
+        QueryMsg::NextBatchTime {} => to_binary(&query_next_batch_time(deps)?),
     }
 }
 
@@ -483,11 +695,90 @@ fn query_white_validators(deps: Deps) -> StdResult<WhitelistedValidatorsResponse
     Ok(response)
 }
 
+/// Returns the target and actual delegation share of every whitelisted validator
+fn query_validator_weights(deps: Deps, env: Env) -> StdResult<ValidatorWeightsResponse> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let delegations: Vec<(String, Uint128)> = deps
+        .querier
+        .query_all_delegations(env.contract.address)?
+        .into_iter()
+        .filter(|d| d.amount.denom == params.underlying_coin_denom)
+        .map(|d| (d.validator, d.amount.amount))
+        .collect();
+    let total_delegated: Uint128 = delegations.iter().map(|(_, amount)| *amount).sum();
+
+    let mut weights: Vec<(String, u64)> = vec![];
+    for val in read_valid_validators(deps.storage)? {
+        let weight = read_validator_weight(deps.storage, &val)?;
+        weights.push((val.to_string(), weight));
+    }
+    let total_weight: u64 = weights.iter().map(|(_, weight)| weight).sum();
+
+    let validators = weights
+        .into_iter()
+        .map(|(validator, weight)| {
+            let delegated = delegations
+                .iter()
+                .find(|(val, _)| *val == validator)
+                .map(|(_, amount)| *amount)
+                .unwrap_or_default();
+            let target_share = if total_weight == 0 {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(weight, total_weight)
+            };
+            let actual_share = if total_delegated.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(delegated, total_delegated)
+            };
+            ValidatorWeightResponse {
+                validator,
+                weight,
+                delegated,
+                target_share,
+                actual_share,
+            }
+        })
+        .collect();
+
+    Ok(ValidatorWeightsResponse {
+        total_delegated,
+        validators,
+    })
+}
+
+fn query_validator_statuses(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ValidatorStatusesResponse> {
+    let start_after = start_after.map(Addr::unchecked);
+    let validators = read_validator_statuses(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(validator, status)| status.as_res(validator.to_string()))
+        .collect();
+    Ok(ValidatorStatusesResponse { validators })
+}
+
 fn query_current_batch(deps: Deps) -> StdResult<CurrentBatchResponse> {
     let current_batch = CURRENT_BATCH.load(deps.storage)?;
     Ok(CurrentBatchResponse {
         id: current_batch.id,
         requested_with_fee: current_batch.requested_with_fee,
+        keeper_reward: current_batch.keeper_reward,
+    })
+}
+
+fn query_next_batch_time(deps: Deps) -> StdResult<NextBatchTimeResponse> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let current_batch = CURRENT_BATCH.load(deps.storage)?;
+    Ok(NextBatchTimeResponse {
+        batch_id: current_batch.id,
+        time: expected_batch_time(&state, &params, 0),
+        requested_with_fee: current_batch.requested_with_fee,
+        keeper_reward: current_batch.keeper_reward,
     })
 }
 
@@ -508,8 +799,8 @@ fn query_withdrawable_unbonded(
     Ok(withdrawable)
 }
 
-fn query_params(deps: Deps) -> StdResult<Parameters> {
-    PARAMETERS.load(deps.storage)
+fn query_params(deps: Deps) -> StdResult<ParametersResponse> {
+    Ok(PARAMETERS.load(deps.storage)?.as_res())
 }
 
 /// Returns total c-luna issued.
@@ -537,12 +828,269 @@ fn query_unbond_requests(
     Ok(res)
 }
 
+/// Joins the unbond requests of an address with the history of their batch.
+/// A request is claimable once its batch is older than the unbonding period,
+/// as in WithdrawableUnbonded.
+fn query_user_unbond_status(
+    deps: Deps,
+    env: Env,
+    address: String,
+    start: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UserUnbondStatusResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let params = PARAMETERS.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let historical_time = now.saturating_sub(params.unbonding_period);
+
+    let requests = get_unbond_requests(deps.storage, &addr, start, limit)?
+        .into_iter()
+        .map(
+            |(batch_id, amount)| match read_unbond_history(deps.storage, batch_id) {
+                Ok(history) => UnbondRequestStatusResponse {
+                    batch_id,
+                    amount,
+                    batch_time: Some(history.time),
+                    release_time: history.time + params.unbonding_period,
+                    applied_exchange_rate: Some(history.applied_exchange_rate),
+                    withdraw_rate: Some(history.withdraw_rate),
+                    released: history.released,
+                    claimable: history.time < historical_time,
+                },
+                // the current batch has not been undelegated yet
+                Err(_) => UnbondRequestStatusResponse {
+                    batch_id,
+                    amount,
+                    batch_time: None,
+                    release_time: expected_batch_time(&state, &params, now)
+                        + params.unbonding_period,
+                    applied_exchange_rate: None,
+                    withdraw_rate: None,
+                    released: false,
+                    claimable: false,
+                },
+            },
+        )
+        .collect();
+
+    Ok(UserUnbondStatusResponse { address, requests })
+}
+
+fn query_exchange_rate_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    end_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ExchangeRateHistoryResponse> {
+    let history = read_exchange_rate_history(deps.storage, start_after, end_before, limit)?
+        .iter()
+        .map(|(id, snapshot)| snapshot.as_res(*id))
+        .collect();
+    Ok(ExchangeRateHistoryResponse { history })
+}
+
+fn query_slashing_events(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SlashingEventsResponse> {
+    let events = read_slashing_events(deps.storage, start_after, limit)?
+        .iter()
+        .map(|(id, event)| event.as_res(*id))
+        .collect();
+    Ok(SlashingEventsResponse { events })
+}
+
+fn query_delegations(deps: Deps, env: Env) -> StdResult<DelegationsResponse> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let mut delegations: Vec<DelegationResponse> = read_delegations(deps.storage)?
+        .into_iter()
+        .map(|(validator, expected)| DelegationResponse {
+            validator: validator.to_string(),
+            expected,
+            actual: Uint128::zero(),
+        })
+        .collect();
+
+    // live delegations of validators without a record are listed as well
+    for delegation in deps.querier.query_all_delegations(&env.contract.address)? {
+        if delegation.amount.denom != params.underlying_coin_denom {
+            continue;
+        }
+        match delegations
+            .iter_mut()
+            .find(|d| d.validator == delegation.validator)
+        {
+            Some(d) => d.actual = delegation.amount.amount,
+            None => delegations.push(DelegationResponse {
+                validator: delegation.validator,
+                expected: Uint128::zero(),
+                actual: delegation.amount.amount,
+            }),
+        }
+    }
+
+    Ok(DelegationsResponse { delegations })
+}
+
+/// Runs the bond math of `_execute_bond` against the current state
+fn query_simulate_bond(deps: Deps, env: Env, amount: Uint128) -> StdResult<SimulateBondResponse> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    simulate_slashing(deps, &env, &mut state, &params)?;
+
+    let total_supply = query_total_issued(deps).unwrap_or_default();
+    let requested_with_fee = CURRENT_BATCH.load(deps.storage)?.requested_with_fee;
+    let BondAmounts {
+        minted,
+        peg_fee,
+        protocol_fee,
+    } = compute_bond_amounts(&state, &params, total_supply, requested_with_fee, amount)?;
+
+    Ok(SimulateBondResponse {
+        minted,
+        peg_recovery_fee: peg_fee,
+        protocol_fee,
+        exchange_rate: state.exchange_rate,
+    })
+}
+
+/// Runs the unbond math of `execute_unbond` against the current state. The
+/// received amount is estimated at the exchange rate after the unbond and
+/// does not include slashing during the unbonding period.
+fn query_simulate_unbond(
+    deps: Deps,
+    env: Env,
+    amount: Uint128,
+) -> StdResult<SimulateUnbondResponse> {
+    let params = PARAMETERS.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+    simulate_slashing(deps, &env, &mut state, &params)?;
+
+    let total_supply = query_total_issued(deps).unwrap_or_default();
+    let requested_with_fee = CURRENT_BATCH.load(deps.storage)?.requested_with_fee;
+    let UnbondAmounts {
+        burnt,
+        requested,
+        peg_fee,
+        protocol_fee,
+        keeper_fee,
+    } = compute_unbond_amounts(&state, &params, total_supply, requested_with_fee, amount)?;
+
+    let release_time =
+        expected_batch_time(&state, &params, env.block.time.seconds()) + params.unbonding_period;
+    state.update_exchange_rate(
+        total_supply.checked_sub(burnt)?,
+        requested_with_fee + requested,
+    );
+
+    Ok(SimulateUnbondResponse {
+        burnt,
+        peg_recovery_fee: peg_fee,
+        protocol_fee,
+        keeper_fee,
+        received: requested * state.exchange_rate,
+        release_time,
+    })
+}
+
+/// Runs the withdraw rate processing of `execute_withdraw_unbonded` without
+/// storing it, and estimates the requests that are still unbonding.
+fn query_simulate_withdraw(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<SimulateWithdrawResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let params = PARAMETERS.load(deps.storage)?;
+    let now = env.block.time.seconds();
+    let historical_time = now.saturating_sub(params.unbonding_period);
+
+    let vault_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &*params.underlying_coin_denom)?
+        .amount;
+    let (released, state) = compute_withdraw_rates(deps.storage, historical_time, vault_balance)?;
+
+    let mut received = Uint128::zero();
+    let mut pending = Uint128::zero();
+    let mut next_release_time: Option<u64> = None;
+    for (batch_id, amount) in get_unbond_requests(deps.storage, &addr, None, None)? {
+        let history = match released.iter().find(|(id, _)| *id == batch_id) {
+            Some((_, history)) => Some(history.clone()),
+            None => read_unbond_history(deps.storage, batch_id).ok(),
+        };
+        let release_time = match history {
+            Some(history) if history.released => {
+                received += amount * history.withdraw_rate;
+                continue;
+            }
+            Some(history) => {
+                pending += amount * history.applied_exchange_rate;
+                history.time + params.unbonding_period
+            }
+            // the current batch has not been undelegated yet
+            None => {
+                pending += amount * state.exchange_rate;
+                expected_batch_time(&state, &params, now) + params.unbonding_period
+            }
+        };
+        next_release_time = Some(next_release_time.map_or(release_time, |t| t.min(release_time)));
+    }
+
+    Ok(SimulateWithdrawResponse {
+        received,
+        pending,
+        next_release_time,
+    })
+}
+
+fn query_operator_approval(
+    deps: Deps,
+    owner: String,
+    operator: String,
+) -> StdResult<OperatorApprovalResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    let approval = OPERATORS.may_load(deps.storage, (&owner_addr, &operator_addr))?;
+    Ok(OperatorApprovalResponse {
+        owner,
+        operator,
+        approved: approval.is_some(),
+        recipient: approval
+            .and_then(|approval| approval.recipient)
+            .map(|recipient| recipient.to_string()),
+    })
+}
+
+fn query_unbond_claim(
+    deps: Deps,
+    address: String,
+    batch_id: u64,
+) -> StdResult<UnbondClaimResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let amount = read_unbond_wait_list(deps.storage, batch_id, &addr).map_err(|_| {
+        StdError::generic_err(format!("No unbond claim found for batch {}", batch_id))
+    })?;
+    let released = read_unbond_history(deps.storage, batch_id)
+        .map(|history| history.released)
+        .unwrap_or(false);
+    Ok(UnbondClaimResponse {
+        address,
+        batch_id,
+        amount,
+        released,
+    })
+}
+
 fn query_unbond_requests_limitation(
     deps: Deps,
     start: Option<u64>,
     limit: Option<u32>,
+    released: Option<bool>,
 ) -> StdResult<AllHistoryResponse> {
-    let requests = all_unbond_history(deps.storage, start, limit)?;
+    let requests = all_unbond_history(deps.storage, start, limit, released)?;
     let requests_res = requests.iter().map(|item| item.as_res()).collect();
     let res = AllHistoryResponse {
         history: requests_res,
@@ -0,0 +1,453 @@
+use crate::state::{
+    is_valid_validator, move_delegation, read_delegations, read_validator_weight, read_validators,
+    remove_white_validators, RebalanceEpoch, ValidatorStatus, CONFIG, DELEGATIONS, PARAMETERS,
+    REBALANCE_EPOCH, VALIDATOR_STATUS,
+};
+use cosmwasm_std::{
+    attr, to_binary, Addr, Coin, CosmosMsg, Deps, DepsMut, Env, Response, StakingMsg, StdError,
+    StdResult, SubMsg, Uint128, WasmMsg,
+};
+use prism_protocol::vault::{DeregistrationReason, ExecuteMsg};
+use std::cmp::Reverse;
+
+const DEFAULT_REBALANCE_MOVES: u32 = 5;
+const MAX_REBALANCE_MOVES: u32 = 10;
+
+/// Picks the validators a bond of `amount` is delegated to.
+/// The amount is split across the whitelisted validators in proportion to how
+/// far each of them is below its target weight once the bond is added.
+/// Validators that can not be found in the validator set (e.g. jailed) are skipped.
+/// Delegated amounts are read from the expected delegations.
+/// Returns (validator, amount) pairs, zero amounts are left out.
+pub(crate) fn pick_bond_validators(deps: Deps, amount: Uint128) -> StdResult<Vec<(Addr, Uint128)>> {
+    let validators = read_validators(deps.storage)?;
+    if validators.is_empty() {
+        return Err(StdError::generic_err(
+            "There are not validators to pick from",
+        ));
+    }
+
+    // (validator, weight, delegated amount)
+    let mut candidates: Vec<(Addr, u64, Uint128)> = vec![];
+    for val in validators {
+        if deps.querier.query_validator(&val)?.is_none() {
+            continue;
+        }
+
+        let weight = read_validator_weight(deps.storage, &val)?;
+        let delegated = DELEGATIONS
+            .may_load(deps.storage, &val)?
+            .unwrap_or_default();
+        candidates.push((val, weight, delegated));
+    }
+
+    if candidates.is_empty() {
+        return Err(StdError::generic_err("All validators are jailed"));
+    }
+
+    let total_weight: u64 = candidates.iter().map(|(_, weight, _)| weight).sum();
+    if total_weight == 0 {
+        return Err(StdError::generic_err(
+            "All available validators have a zero weight",
+        ));
+    }
+
+    let total_delegated: Uint128 = candidates.iter().map(|(_, _, d)| *d).sum::<Uint128>() + amount;
+
+    // distance of every validator to its target once the bond is added
+    let mut shares: Vec<Uint128> = candidates
+        .iter()
+        .map(|(_, weight, delegated)| {
+            total_delegated
+                .multiply_ratio(*weight, total_weight)
+                .saturating_sub(*delegated)
+        })
+        .collect();
+
+    // every validator is already at its target (only possible due to rounding),
+    // so fall back to splitting by weight
+    if shares.iter().all(|share| share.is_zero()) {
+        shares = candidates
+            .iter()
+            .map(|(_, weight, _)| Uint128::from(*weight))
+            .collect();
+    }
+
+    Ok(candidates
+        .into_iter()
+        .zip(split_proportionally(amount, &shares))
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|((val, _, _), amount)| (val, amount))
+        .collect())
+}
+
+/// Picks the validators `claim` is undelegated from.
+/// Validators are drained starting from the most over-weight one, where the
+/// target weights are computed over the delegations left after the undelegation.
+/// Delegations to validators that are not whitelisted have a target of zero.
+/// Delegated amounts are read from the expected delegations, so the caller
+/// must have resynced them (see `slashing`).
+/// Returns (validator, amount) pairs, zero amounts are left out.
+pub(crate) fn pick_unbond_validators(
+    deps: Deps,
+    claim: Uint128,
+) -> StdResult<Vec<(Addr, Uint128)>> {
+    // (validator, delegated amount)
+    let delegations = read_delegations(deps.storage)?;
+
+    let total_delegated: Uint128 = delegations.iter().map(|(_, d)| *d).sum();
+    let remaining = total_delegated.checked_sub(claim).map_err(|_| {
+        StdError::generic_err("The undelegation amount is bigger than the total delegated amount")
+    })?;
+
+    let total_weight: u64 = read_validators(deps.storage)?
+        .iter()
+        .map(|val| read_validator_weight(deps.storage, val))
+        .sum::<StdResult<u64>>()?;
+
+    // (validator, delegated amount, amount above target)
+    let mut excesses: Vec<(Addr, Uint128, Uint128)> = vec![];
+    for (val, delegated) in delegations {
+        let target = if total_weight == 0 || !is_valid_validator(deps.storage, &val)? {
+            Uint128::zero()
+        } else {
+            remaining.multiply_ratio(read_validator_weight(deps.storage, &val)?, total_weight)
+        };
+        excesses.push((val, delegated, delegated.saturating_sub(target)));
+    }
+    excesses.sort_by_key(|(_, _, excess)| Reverse(*excess));
+
+    let mut claimed = claim;
+    let mut undelegations: Vec<(Addr, Uint128)> = vec![];
+
+    // take the excess from the most over-weight validators first
+    for (val, _, excess) in excesses.iter() {
+        if claimed.is_zero() {
+            break;
+        }
+        let amount = Uint128::min(*excess, claimed);
+        if !amount.is_zero() {
+            undelegations.push((val.clone(), amount));
+            claimed = claimed.checked_sub(amount)?;
+        }
+    }
+
+    // rounding may leave a few micro units behind, take them from whatever
+    // delegation is left in the same order
+    for (val, delegated, excess) in excesses.iter() {
+        if claimed.is_zero() {
+            break;
+        }
+        let taken = Uint128::min(*excess, *delegated);
+        let amount = Uint128::min(delegated.checked_sub(taken)?, claimed);
+        if amount.is_zero() {
+            continue;
+        }
+        claimed = claimed.checked_sub(amount)?;
+        match undelegations.iter_mut().find(|(v, _)| v == val) {
+            Some((_, undelegated)) => *undelegated += amount,
+            None => undelegations.push((val.clone(), amount)),
+        }
+    }
+
+    Ok(undelegations)
+}
+
+/// Deregisters whitelisted validators that left the active validator set
+/// (jailed, tombstoned or unbonded) or whose commission is above
+/// `commission_ceiling`. The reason is kept in the validator status map and
+/// their stake is redelegated to the remaining validators towards the target
+/// weights.
+/// Permissionless
+pub fn execute_check_validators(deps: DepsMut, env: Env) -> StdResult<Response> {
+    CONFIG.load(deps.storage)?.assert_initialized()?;
+    let params = PARAMETERS.load(deps.storage)?;
+    let validators = read_validators(deps.storage)?;
+
+    let mut unhealthy: Vec<(Addr, DeregistrationReason)> = vec![];
+    for val in validators.iter() {
+        match deps.querier.query_validator(val)? {
+            // a jailed validator drops out of the validator set, but the
+            // vault delegation to it stays until it is redelegated
+            None => {
+                let reason = match deps.querier.query_delegation(&env.contract.address, val)? {
+                    Some(_) => DeregistrationReason::Jailed {},
+                    None => DeregistrationReason::Missing {},
+                };
+                unhealthy.push((val.clone(), reason));
+            }
+            Some(v) if v.commission > params.commission_ceiling => unhealthy.push((
+                val.clone(),
+                DeregistrationReason::HighCommission {
+                    commission: v.commission,
+                },
+            )),
+            Some(_) => {}
+        }
+    }
+
+    let mut attributes = vec![attr("action", "check_validators")];
+    if unhealthy.is_empty() {
+        return Ok(Response::new().add_attributes(attributes));
+    }
+
+    if unhealthy.len() == validators.len() {
+        return Err(StdError::generic_err(
+            "There are no healthy validators to redelegate to",
+        ));
+    }
+
+    // deregister first, so the stake is only moved to healthy validators
+    for (val, reason) in unhealthy.iter() {
+        remove_white_validators(deps.storage, val)?;
+        VALIDATOR_STATUS.save(
+            deps.storage,
+            val,
+            &ValidatorStatus {
+                reason: reason.clone(),
+                time: env.block.time.seconds(),
+            },
+        )?;
+    }
+
+    let mut messages: Vec<SubMsg> = vec![];
+    for (val, reason) in unhealthy {
+        let reason = match reason {
+            DeregistrationReason::Missing {} => "missing",
+            DeregistrationReason::Jailed {} => "jailed",
+            DeregistrationReason::HighCommission { .. } => "high_commission",
+        };
+        attributes.push(attr("deregistered_validator", val.as_str()));
+        attributes.push(attr("reason", reason));
+
+        // stake that is still being redelegated is moved by a later Rebalance
+        let movable = deps
+            .querier
+            .query_delegation(&env.contract.address, &val)?
+            .map(|d| d.can_redelegate.amount)
+            .unwrap_or_default();
+        if movable.is_zero() {
+            continue;
+        }
+
+        for (dst, amount) in pick_bond_validators(deps.as_ref(), movable)? {
+            move_delegation(deps.storage, &val, &dst, amount)?;
+            messages.push(SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator: val.to_string(),
+                dst_validator: dst.to_string(),
+                amount: Coin::new(amount.u128(), &params.underlying_coin_denom),
+            })));
+        }
+    }
+
+    // redelegations withdraw the pending rewards, have them processed
+    if !messages.is_empty() {
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::UpdateGlobalIndex {
+                airdrop_hooks: None,
+            })?,
+            funds: vec![],
+        })));
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(attributes))
+}
+
+/// Redelegates from over-weight validators to under-weight ones, at most
+/// `max_moves` redelegations per call.
+/// Only `can_redelegate` is taken from a source validator, so stake that was
+/// redelegated to it is not moved again before that redelegation matures.
+/// At most `rebalance_cap` of the total delegation is moved per epoch.
+/// Permissionless
+pub fn execute_rebalance(deps: DepsMut, env: Env, max_moves: Option<u32>) -> StdResult<Response> {
+    CONFIG.load(deps.storage)?.assert_initialized()?;
+    let params = PARAMETERS.load(deps.storage)?;
+    let max_moves = max_moves
+        .unwrap_or(DEFAULT_REBALANCE_MOVES)
+        .min(MAX_REBALANCE_MOVES) as usize;
+
+    // start a new epoch once the previous one is over
+    let now = env.block.time.seconds();
+    let mut epoch = REBALANCE_EPOCH.may_load(deps.storage)?.unwrap_or_default();
+    if now >= epoch.start + params.epoch_period {
+        epoch = RebalanceEpoch {
+            start: now,
+            redelegated: Uint128::zero(),
+        };
+    }
+
+    // (validator, delegated amount)
+    let delegations: Vec<(Addr, Uint128)> = deps
+        .querier
+        .query_all_delegations(&env.contract.address)?
+        .into_iter()
+        .filter(|d| d.amount.denom == params.underlying_coin_denom)
+        .map(|d| (Addr::unchecked(d.validator), d.amount.amount))
+        .collect();
+    let total_delegated: Uint128 = delegations.iter().map(|(_, d)| *d).sum();
+
+    let mut available = (total_delegated * params.rebalance_cap).saturating_sub(epoch.redelegated);
+    if available.is_zero() {
+        return Err(StdError::generic_err(
+            "The rebalance cap of the current epoch has been reached",
+        ));
+    }
+
+    let validators = read_validators(deps.storage)?;
+    let total_weight: u64 = validators
+        .iter()
+        .map(|val| read_validator_weight(deps.storage, val))
+        .sum::<StdResult<u64>>()?;
+    let target_of = |val: &Addr| -> StdResult<Uint128> {
+        if total_weight == 0 || !is_valid_validator(deps.storage, val)? {
+            return Ok(Uint128::zero());
+        }
+        let weight = read_validator_weight(deps.storage, val)?;
+        Ok(total_delegated.multiply_ratio(weight, total_weight))
+    };
+
+    // (validator, amount that can be moved out)
+    let mut sources: Vec<(Addr, Uint128)> = vec![];
+    for (val, delegated) in delegations.iter() {
+        let excess = delegated.saturating_sub(target_of(val)?);
+        if excess.is_zero() {
+            continue;
+        }
+        let can_redelegate = deps
+            .querier
+            .query_delegation(&env.contract.address, val)?
+            .map(|d| d.can_redelegate.amount)
+            .unwrap_or_default();
+        let movable = Uint128::min(excess, can_redelegate);
+        if !movable.is_zero() {
+            sources.push((val.clone(), movable));
+        }
+    }
+
+    // (validator, amount missing to reach its target)
+    let mut destinations: Vec<(Addr, Uint128)> = vec![];
+    for val in validators {
+        if deps.querier.query_validator(&val)?.is_none() {
+            continue;
+        }
+        let delegated = delegations
+            .iter()
+            .find(|(v, _)| *v == val)
+            .map(|(_, d)| *d)
+            .unwrap_or_default();
+        let deficit = target_of(&val)?.saturating_sub(delegated);
+        if !deficit.is_zero() {
+            destinations.push((val, deficit));
+        }
+    }
+
+    sources.sort_by_key(|(_, movable)| Reverse(*movable));
+    destinations.sort_by_key(|(_, deficit)| Reverse(*deficit));
+
+    // pair the biggest excesses with the biggest deficits
+    let mut moves: Vec<(Addr, Addr, Uint128)> = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < sources.len()
+        && j < destinations.len()
+        && moves.len() < max_moves
+        && !available.is_zero()
+    {
+        let amount = Uint128::min(Uint128::min(sources[i].1, destinations[j].1), available);
+        moves.push((sources[i].0.clone(), destinations[j].0.clone(), amount));
+
+        sources[i].1 = sources[i].1.checked_sub(amount)?;
+        destinations[j].1 = destinations[j].1.checked_sub(amount)?;
+        available = available.checked_sub(amount)?;
+        if sources[i].1.is_zero() {
+            i += 1;
+        }
+        if destinations[j].1.is_zero() {
+            j += 1;
+        }
+    }
+
+    if moves.is_empty() {
+        return Err(StdError::generic_err("Delegations are already balanced"));
+    }
+
+    let redelegated: Uint128 = moves.iter().map(|(_, _, amount)| *amount).sum();
+    epoch.redelegated += redelegated;
+    REBALANCE_EPOCH.save(deps.storage, &epoch)?;
+    for (src, dst, amount) in &moves {
+        move_delegation(deps.storage, src, dst, *amount)?;
+    }
+
+    let mut messages: Vec<SubMsg> = moves
+        .iter()
+        .map(|(src, dst, amount)| {
+            SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator: src.to_string(),
+                dst_validator: dst.to_string(),
+                amount: Coin::new(amount.u128(), &params.underlying_coin_denom),
+            }))
+        })
+        .collect();
+
+    // redelegations withdraw the pending rewards, have them processed
+    messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::UpdateGlobalIndex {
+            airdrop_hooks: None,
+        })?,
+        funds: vec![],
+    })));
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "rebalance"),
+            attr("moves", moves.len().to_string()),
+            attr("redelegated_amount", redelegated),
+        ]))
+}
+
+/// Splits `amount` in proportion to `shares`.
+/// The rounding leftover is given to the biggest share.
+fn split_proportionally(amount: Uint128, shares: &[Uint128]) -> Vec<Uint128> {
+    let total: Uint128 = shares.iter().sum();
+    if total.is_zero() {
+        return vec![Uint128::zero(); shares.len()];
+    }
+
+    let mut parts: Vec<Uint128> = shares
+        .iter()
+        .map(|share| amount.multiply_ratio(*share, total))
+        .collect();
+
+    let distributed: Uint128 = parts.iter().sum();
+    let mut biggest = 0;
+    for (i, share) in shares.iter().enumerate() {
+        if *share > shares[biggest] {
+            biggest = i;
+        }
+    }
+    parts[biggest] += amount - distributed;
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_proportionally() {
+        let shares = vec![Uint128::new(1), Uint128::new(2), Uint128::new(3)];
+        let parts = split_proportionally(Uint128::new(100), &shares);
+        assert_eq!(
+            parts,
+            vec![Uint128::new(16), Uint128::new(33), Uint128::new(51)]
+        );
+
+        let parts = split_proportionally(Uint128::new(100), &[Uint128::zero(); 2]);
+        assert_eq!(parts, vec![Uint128::zero(); 2]);
+    }
+}
@@ -3,6 +3,7 @@ pub mod state;
 
 mod bond;
 mod config;
+mod delegation;
 mod math;
 mod refract;
 mod unbond;
@@ -1,33 +1,72 @@
-use crate::state::CONFIG;
+use crate::state::{assert_not_paused, Config, CONFIG};
 use cosmwasm_std::{
-    to_binary, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, WasmMsg,
+    to_binary, Addr, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg as TokenMsg;
 
 pub fn split(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> StdResult<Response> {
     let config = CONFIG.load(deps.storage)?.assert_initialized()?;
+    assert_not_paused(deps.storage, |p| p.refraction, "refraction")?;
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.cluna_contract.to_string(),
+        msg: to_binary(&TokenMsg::TransferFrom {
+            owner: info.sender.to_string(),
+            recipient: env.contract.address.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    })];
+    messages.extend(mint_refracted(&config, &info.sender, amount)?);
+    Ok(Response::new().add_messages(messages))
+}
+
+/// This message must be called by receive_cw20
+/// The cLuna was already sent to the vault, only yLuna and pLuna are minted
+pub(crate) fn split_received(
+    deps: DepsMut,
+    amount: Uint128,
+    sender: String, // human who sent the c-luna to us
+    recipient: Option<String>,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?.assert_initialized()?;
+    assert_not_paused(deps.storage, |p| p.refraction, "refraction")?;
+    let recipient = deps.api.addr_validate(&recipient.unwrap_or(sender))?;
+    Ok(Response::new().add_messages(mint_refracted(&config, &recipient, amount)?))
+}
+
+pub fn merge(
+    deps: DepsMut,
+    info: MessageInfo,
+    amount: Uint128,
+    recipient: Option<String>,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?.assert_initialized()?;
+    assert_not_paused(deps.storage, |p| p.refraction, "refraction")?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
     let messages = vec![
         CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.cluna_contract.to_string(),
-            msg: to_binary(&TokenMsg::TransferFrom {
+            contract_addr: config.yluna_contract.to_string(),
+            msg: to_binary(&TokenMsg::BurnFrom {
                 owner: info.sender.to_string(),
-                recipient: env.contract.address.to_string(),
                 amount,
             })?,
             funds: vec![],
         }),
         CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.yluna_contract.to_string(),
-            msg: to_binary(&TokenMsg::Mint {
-                recipient: info.sender.to_string(),
+            contract_addr: config.pluna_contract.to_string(),
+            msg: to_binary(&TokenMsg::BurnFrom {
+                owner: info.sender.to_string(),
                 amount,
             })?,
             funds: vec![],
         }),
         CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.pluna_contract.to_string(),
-            msg: to_binary(&TokenMsg::Mint {
-                recipient: info.sender.to_string(),
+            contract_addr: config.cluna_contract.to_string(),
+            msg: to_binary(&TokenMsg::Transfer {
+                recipient: recipient.to_string(),
                 amount,
             })?,
             funds: vec![],
@@ -36,33 +75,24 @@ pub fn split(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> Std
     Ok(Response::new().add_messages(messages))
 }
 
-pub fn merge(deps: DepsMut, info: MessageInfo, amount: Uint128) -> StdResult<Response> {
-    let config = CONFIG.load(deps.storage)?.assert_initialized()?;
-    let messages = vec![
+/// Mint `amount` of yLuna and pLuna to the recipient
+fn mint_refracted(config: &Config, recipient: &Addr, amount: Uint128) -> StdResult<Vec<CosmosMsg>> {
+    Ok(vec![
         CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: config.yluna_contract.to_string(),
-            msg: to_binary(&TokenMsg::BurnFrom {
-                owner: info.sender.to_string(),
+            msg: to_binary(&TokenMsg::Mint {
+                recipient: recipient.to_string(),
                 amount,
             })?,
             funds: vec![],
         }),
         CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: config.pluna_contract.to_string(),
-            msg: to_binary(&TokenMsg::BurnFrom {
-                owner: info.sender.to_string(),
-                amount,
-            })?,
-            funds: vec![],
-        }),
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.cluna_contract.to_string(),
-            msg: to_binary(&TokenMsg::Transfer {
-                recipient: info.sender.to_string(),
+            msg: to_binary(&TokenMsg::Mint {
+                recipient: recipient.to_string(),
                 amount,
             })?,
             funds: vec![],
         }),
-    ];
-    Ok(Response::new().add_messages(messages))
+    ])
 }
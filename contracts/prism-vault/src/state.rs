@@ -6,7 +6,11 @@ use cw_storage_plus::{Bound, Item, Map, U64Key};
 
 use prism_protocol::{
     internal::de::deserialize_key,
-    vault::{ConfigResponse, StateResponse, UnbondHistoryResponse},
+    vault::{
+        ConfigResponse, DeregistrationReason, ExchangeRateSnapshotResponse, ParametersResponse,
+        PauseStatusResponse, SlashingEventResponse, StateResponse, UnbondHistoryResponse,
+        ValidatorStatusResponse,
+    },
 };
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -16,12 +20,42 @@ pub struct Parameters {
     pub unbonding_period: u64,     // as a duration in seconds
     pub peg_recovery_fee: Decimal, // must be in [0, 1].
     pub er_threshold: Decimal,     // exchange rate threshold. Must be in [0, 1].
+    #[serde(default = "default_rebalance_cap")]
+    pub rebalance_cap: Decimal, // share of the total delegation Rebalance can move per epoch. Must be in [0, 1].
+    #[serde(default = "Decimal::one")]
+    pub commission_ceiling: Decimal, // validators above it are deregistered by CheckValidators. Must be in [0, 1].
+    #[serde(default)]
+    pub mint_fee: Decimal, // share of minted cLuna sent to the collector. At most MAX_PROTOCOL_FEE.
+    #[serde(default)]
+    pub unbond_fee: Decimal, // share of unbonded cLuna sent to the collector. At most MAX_PROTOCOL_FEE.
+    #[serde(default)]
+    pub keeper_fee: Decimal, // share of unbonded cLuna paid to the batch processor. At most MAX_PROTOCOL_FEE.
+}
+
+impl Parameters {
+    pub fn as_res(&self) -> ParametersResponse {
+        ParametersResponse {
+            epoch_period: self.epoch_period,
+            underlying_coin_denom: self.underlying_coin_denom.clone(),
+            unbonding_period: self.unbonding_period,
+            peg_recovery_fee: self.peg_recovery_fee,
+            er_threshold: self.er_threshold,
+            rebalance_cap: self.rebalance_cap,
+            commission_ceiling: self.commission_ceiling,
+            mint_fee: self.mint_fee,
+            unbond_fee: self.unbond_fee,
+            keeper_fee: self.keeper_fee,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CurrentBatch {
     pub id: u64,
     pub requested_with_fee: Uint128,
+    // keeper fees of the batch's requests, held by the vault as cLuna
+    #[serde(default)]
+    pub keeper_reward: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
@@ -58,6 +92,101 @@ impl State {
     }
 }
 
+/// Why and when a validator was deregistered by CheckValidators
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorStatus {
+    pub reason: DeregistrationReason,
+    pub time: u64,
+}
+
+impl ValidatorStatus {
+    pub fn as_res(&self, validator: String) -> ValidatorStatusResponse {
+        ValidatorStatusResponse {
+            validator,
+            reason: self.reason.clone(),
+            time: self.time,
+        }
+    }
+}
+
+/// Exchange rate recorded at UpdateGlobalIndex, slashing and batch processing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateSnapshot {
+    pub time: u64,
+    pub exchange_rate: Decimal,
+    pub total_bond_amount: Uint128,
+}
+
+impl ExchangeRateSnapshot {
+    pub fn as_res(&self, id: u64) -> ExchangeRateSnapshotResponse {
+        ExchangeRateSnapshotResponse {
+            id,
+            time: self.time,
+            exchange_rate: self.exchange_rate,
+            total_bond_amount: self.total_bond_amount,
+        }
+    }
+}
+
+/// A validator delegation found below the expected amount
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlashingEvent {
+    pub validator: Addr,
+    pub expected_delegation: Uint128,
+    pub actual_delegation: Uint128,
+    pub time: u64,
+    pub rate_before: Decimal,
+    pub rate_after: Decimal,
+}
+
+impl SlashingEvent {
+    pub fn as_res(&self, id: u64) -> SlashingEventResponse {
+        SlashingEventResponse {
+            id,
+            validator: self.validator.to_string(),
+            expected_delegation: self.expected_delegation,
+            actual_delegation: self.actual_delegation,
+            time: self.time,
+            rate_before: self.rate_before,
+            rate_after: self.rate_after,
+        }
+    }
+}
+
+/// Withdrawal rights an owner granted to an operator
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorApproval {
+    // recipient of the withdrawn coins, the owner if not set
+    pub recipient: Option<Addr>,
+}
+
+/// Redelegations done by Rebalance in the current epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct RebalanceEpoch {
+    pub start: u64,
+    pub redelegated: Uint128,
+}
+
+/// Operation categories the guardian has halted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PauseStatus {
+    pub minting: bool,
+    pub refraction: bool,
+    pub unbonding: bool,
+    pub withdrawals: bool,
+}
+
+impl PauseStatus {
+    pub fn as_res(&self) -> PauseStatusResponse {
+        PauseStatusResponse {
+            minting: self.minting,
+            refraction: self.refraction,
+            unbonding: self.unbonding,
+            withdrawals: self.withdrawals,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     // owner is the address of the owner of the Vault. It is used to
@@ -86,6 +215,30 @@ pub struct Config {
     pub token_admin: Addr,
     pub token_code_id: u64,
     pub manager: Addr,
+    // prismswap_router is used to swap cLuna for Luna on InstantUnbond. Not
+    // set until the owner configures it.
+    #[serde(default)]
+    pub prismswap_router: Option<Addr>,
+    // guardian can pause and unpause categories of operations in an
+    // emergency. Not set until the owner configures it.
+    #[serde(default)]
+    pub guardian: Option<Addr>,
+    // underlying_symbol names the c/p/y tokens, e.g. "Luna" for cLuna, pLuna
+    // and yLuna.
+    #[serde(default = "default_underlying_symbol")]
+    pub underlying_symbol: String,
+    // collector receives the mint and unbond fees as cLuna. Not set until
+    // the owner configures it.
+    #[serde(default)]
+    pub collector: Option<Addr>,
+}
+
+pub fn default_underlying_symbol() -> String {
+    "Luna".to_string()
+}
+
+pub fn default_rebalance_cap() -> Decimal {
+    Decimal::percent(10)
 }
 
 impl Config {
@@ -99,6 +252,10 @@ impl Config {
             airdrop_registry_contract: self.airdrop_registry_contract.to_string(),
             initialized: self.initialized,
             manager: self.manager.to_string(),
+            prismswap_router: self.prismswap_router.as_ref().map(|r| r.to_string()),
+            guardian: self.guardian.as_ref().map(|g| g.to_string()),
+            underlying_symbol: self.underlying_symbol.clone(),
+            collector: self.collector.as_ref().map(|c| c.to_string()),
         }
     }
 
@@ -142,9 +299,46 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub const PARAMETERS: Item<Parameters> = Item::new("parameters");
 pub const CURRENT_BATCH: Item<CurrentBatch> = Item::new("current_batch");
 pub const STATE: Item<State> = Item::new("state");
+pub const REBALANCE_EPOCH: Item<RebalanceEpoch> = Item::new("rebalance_epoch");
+pub const PAUSE_STATUS: Item<PauseStatus> = Item::new("pause_status");
 pub const UNBOND_WAITLIST: Map<(&Addr, U64Key), Uint128> = Map::new("unbond_waitlist");
+/// Keeper fees paid by the unbond requests of a user in a batch, refunded
+/// when the requests are cancelled
+pub const UNBOND_KEEPER_FEES: Map<(&Addr, U64Key), Uint128> = Map::new("unbond_keeper_fees");
 pub const UNBOND_HISTORY: Map<U64Key, UnbondHistory> = Map::new("unbond_history");
 pub const VALIDATORS: Map<&Addr, bool> = Map::new("validators");
+pub const VALIDATOR_WEIGHTS: Map<&Addr, u64> = Map::new("validator_weights");
+pub const VALIDATOR_STATUS: Map<&Addr, ValidatorStatus> = Map::new("validator_status");
+/// Keyed by a sequence number, so several snapshots of the same block are kept
+pub const EXCHANGE_RATE_HISTORY: Map<U64Key, ExchangeRateSnapshot> =
+    Map::new("exchange_rate_history");
+pub const EXCHANGE_RATE_SNAPSHOT_COUNT: Item<u64> = Item::new("exchange_rate_snapshot_count");
+/// Delegation the vault expects to hold per validator, kept in line with the
+/// staking messages it sends and resynced to the chain by slashing checks
+pub const DELEGATIONS: Map<&Addr, Uint128> = Map::new("delegations");
+pub const SLASHING_EVENTS: Map<U64Key, SlashingEvent> = Map::new("slashing_events");
+pub const SLASHING_EVENT_COUNT: Item<u64> = Item::new("slashing_event_count");
+/// (owner, operator) -> approval
+pub const OPERATORS: Map<(&Addr, &Addr), OperatorApproval> = Map::new("operators");
+
+/// Weight assumed for whitelisted validators that were never given one.
+pub const DEFAULT_VALIDATOR_WEIGHT: u64 = 100;
+
+pub fn read_pause_status(storage: &dyn Storage) -> StdResult<PauseStatus> {
+    Ok(PAUSE_STATUS.may_load(storage)?.unwrap_or_default())
+}
+
+/// Fails with a Paused error if the guardian halted the operation category
+pub fn assert_not_paused(
+    storage: &dyn Storage,
+    category: fn(&PauseStatus) -> bool,
+    name: &str,
+) -> StdResult<()> {
+    if category(&read_pause_status(storage)?) {
+        return Err(StdError::generic_err(format!("Paused: {}", name)));
+    }
+    Ok(())
+}
 
 /// Store undelegation wait list per each batch
 /// HashMap<user's address + batch_id, requested_amount>
@@ -172,10 +366,31 @@ pub fn remove_unbond_wait_list(
 ) -> StdResult<()> {
     for b in batch_id {
         UNBOND_WAITLIST.remove(storage, (sender_addr, b.into()));
+        UNBOND_KEEPER_FEES.remove(storage, (sender_addr, b.into()));
     }
     Ok(())
 }
 
+/// Add the keeper fee of an unbond request to the user's fees in the batch
+pub fn store_unbond_keeper_fee(
+    storage: &mut dyn Storage,
+    batch_id: u64,
+    sender_addr: &Addr,
+    keeper_fee: Uint128,
+) -> StdResult<()> {
+    if keeper_fee.is_zero() {
+        return Ok(());
+    }
+    UNBOND_KEEPER_FEES.update(
+        storage,
+        (sender_addr, batch_id.into()),
+        |existing_fee: Option<Uint128>| -> StdResult<_> {
+            Ok(existing_fee.unwrap_or_default() + keeper_fee)
+        },
+    )?;
+    Ok(())
+}
+
 pub fn read_unbond_wait_list(
     storage: &dyn Storage,
     batch_id: u64,
@@ -319,9 +534,27 @@ pub fn store_white_validators(storage: &mut dyn Storage, validator_addr: &Addr)
 /// Remove valid validators
 pub fn remove_white_validators(storage: &mut dyn Storage, validator_addr: &Addr) -> StdResult<()> {
     VALIDATORS.remove(storage, validator_addr);
+    VALIDATOR_WEIGHTS.remove(storage, validator_addr);
     Ok(())
 }
 
+/// Store the target delegation weight of a validator
+pub fn store_validator_weight(
+    storage: &mut dyn Storage,
+    validator_addr: &Addr,
+    weight: u64,
+) -> StdResult<()> {
+    VALIDATOR_WEIGHTS.save(storage, validator_addr, &weight)
+}
+
+/// Read the target delegation weight of a validator.
+/// Falls back to DEFAULT_VALIDATOR_WEIGHT when no weight was set.
+pub fn read_validator_weight(storage: &dyn Storage, validator_addr: &Addr) -> StdResult<u64> {
+    Ok(VALIDATOR_WEIGHTS
+        .may_load(storage, validator_addr)?
+        .unwrap_or(DEFAULT_VALIDATOR_WEIGHT))
+}
+
 // Returns all validators
 pub fn read_validators(storage: &dyn Storage) -> StdResult<Vec<Addr>> {
     VALIDATORS
@@ -366,21 +599,169 @@ pub fn all_unbond_history(
     storage: &dyn Storage,
     start: Option<u64>,
     limit: Option<u32>,
+    released: Option<bool>,
 ) -> StdResult<Vec<UnbondHistory>> {
-    let start = U64Key::from(start.unwrap_or_default());
+    let mut start = start.unwrap_or_default();
+    let mut end = None;
+    // batches are released in order up to the last processed one, so the
+    // release status bounds the range of batch ids instead of filtering them
+    if let Some(released) = released {
+        let last_processed_batch = STATE.load(storage)?.last_processed_batch;
+        if released {
+            end = Some(Bound::Inclusive(U64Key::from(last_processed_batch).into()));
+        } else {
+            start = u64::max(start, last_processed_batch);
+        }
+    }
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let res = UNBOND_HISTORY
+    UNBOND_HISTORY
         .range(
             storage,
-            Some(Bound::Exclusive(start.into())),
-            None,
+            Some(Bound::Exclusive(U64Key::from(start).into())),
+            end,
             Order::Ascending,
         )
         .take(limit)
+        .map(|item| item.map(|(_, history)| history))
+        .collect::<StdResult<Vec<UnbondHistory>>>()
+}
+
+/// Return the statuses of validators deregistered by CheckValidators
+pub fn read_validator_statuses(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, ValidatorStatus)>> {
+    let start = start_after.map(|addr| Bound::exclusive(addr.as_bytes()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    VALIDATOR_STATUS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
         .map(|item| {
-            let history: UnbondHistory = item.unwrap().1;
-            Ok(history)
+            let (k, v) = item?;
+            Ok((deserialize_key::<Addr>(k)?, v))
         })
-        .collect();
-    res
+        .collect()
+}
+
+/// Append the current exchange rate to the exchange rate history
+pub fn store_exchange_rate_snapshot(
+    storage: &mut dyn Storage,
+    time: u64,
+    state: &State,
+) -> StdResult<u64> {
+    let id = EXCHANGE_RATE_SNAPSHOT_COUNT
+        .may_load(storage)?
+        .unwrap_or_default()
+        + 1;
+    EXCHANGE_RATE_HISTORY.save(
+        storage,
+        id.into(),
+        &ExchangeRateSnapshot {
+            time,
+            exchange_rate: state.exchange_rate,
+            total_bond_amount: state.total_bond_amount,
+        },
+    )?;
+    EXCHANGE_RATE_SNAPSHOT_COUNT.save(storage, &id)?;
+    Ok(id)
+}
+
+/// Return the exchange rate snapshots with an id between start_after and
+/// end_before
+pub fn read_exchange_rate_history(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    end_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, ExchangeRateSnapshot)>> {
+    let start = start_after.map(|id| Bound::Exclusive(U64Key::from(id).into()));
+    let end = end_before.map(|id| Bound::Exclusive(U64Key::from(id).into()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    EXCHANGE_RATE_HISTORY
+        .range(storage, start, end, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((deserialize_key::<u64>(k)?, v))
+        })
+        .collect()
+}
+
+/// Record a delegation (or undelegation) to the expected delegations
+pub fn add_delegation(
+    storage: &mut dyn Storage,
+    validator: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    DELEGATIONS.update(storage, validator, |delegated| -> StdResult<_> {
+        Ok(delegated.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+/// Delegations made before the expected amounts were recorded are unknown
+/// until the next slashing check, hence saturating
+pub fn sub_delegation(
+    storage: &mut dyn Storage,
+    validator: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let delegated = DELEGATIONS
+        .may_load(storage, validator)?
+        .unwrap_or_default()
+        .saturating_sub(amount);
+    if delegated.is_zero() {
+        DELEGATIONS.remove(storage, validator);
+        Ok(())
+    } else {
+        DELEGATIONS.save(storage, validator, &delegated)
+    }
+}
+
+/// Return the expected delegation of every validator
+pub fn read_delegations(storage: &dyn Storage) -> StdResult<Vec<(Addr, Uint128)>> {
+    DELEGATIONS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((deserialize_key::<Addr>(k)?, v))
+        })
+        .collect()
+}
+
+/// Record a redelegation to the expected delegations
+pub fn move_delegation(
+    storage: &mut dyn Storage,
+    src: &Addr,
+    dst: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    sub_delegation(storage, src, amount)?;
+    add_delegation(storage, dst, amount)
+}
+
+pub fn store_slashing_event(storage: &mut dyn Storage, event: &SlashingEvent) -> StdResult<u64> {
+    let id = SLASHING_EVENT_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    SLASHING_EVENTS.save(storage, id.into(), event)?;
+    SLASHING_EVENT_COUNT.save(storage, &id)?;
+    Ok(id)
+}
+
+/// Return the slashing events recorded after the given id
+pub fn read_slashing_events(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, SlashingEvent)>> {
+    let start = start_after.map(|id| Bound::Exclusive(U64Key::from(id).into()));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    SLASHING_EVENTS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((deserialize_key::<u64>(k)?, v))
+        })
+        .collect()
 }
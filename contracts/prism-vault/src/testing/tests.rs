@@ -1,5 +1,5 @@
 use cosmwasm_std::{
-    attr, coin, from_binary, to_binary, Addr, Api, BankMsg, Coin, ContractResult, CosmosMsg,
+    attr, coin, coins, from_binary, to_binary, Addr, Api, BankMsg, Coin, ContractResult, CosmosMsg,
     Decimal, DepsMut, DistributionMsg, Env, FullDelegation, MessageInfo, OwnedDeps, Querier, Reply,
     ReplyOn, Response, StakingMsg, StdError, StdResult, Storage, SubMsg, SubMsgExecutionResponse,
     Uint128, Validator, WasmMsg,
@@ -14,14 +14,22 @@ use crate::config::MAX_VALIDATORS;
 use crate::contract::{execute, instantiate, query, reply};
 use crate::unbond::execute_unbond;
 use prism_protocol::vault::{
-    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    StateResponse, UnbondRequestsResponse, WhitelistedValidatorsResponse,
-    WithdrawableUnbondedResponse,
+    AllHistoryResponse, ConfigResponse, CurrentBatchResponse, DelegationResponse,
+    DelegationsResponse, DeregistrationReason, ExchangeRateHistoryResponse,
+    ExchangeRateSnapshotResponse, ExecuteMsg, InstantiateMsg, NextBatchTimeResponse,
+    OperatorApprovalResponse, ParametersResponse, PauseStatusResponse, QueryMsg,
+    SimulateBondResponse, SimulateUnbondResponse, SimulateWithdrawResponse, SlashingEventResponse,
+    SlashingEventsResponse, StateResponse, UnbondClaimResponse, UnbondRequestStatusResponse,
+    UnbondRequestsResponse, UserUnbondStatusResponse, ValidatorStatusResponse,
+    ValidatorStatusesResponse, ValidatorWeight, ValidatorWeightResponse, ValidatorWeightsResponse,
+    WhitelistedValidatorsResponse, WithdrawableUnbondedResponse, MAX_PROTOCOL_FEE,
 };
 
 use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use prism_protocol::vault::Cw20HookMsg::Unbond;
+use prism_protocol::vault::Cw20HookMsg::{InstantUnbond, Split, Unbond};
 use prism_protocol::vault::ExecuteMsg::{CheckSlashing, Receive, UpdateConfig, UpdateParams};
+use prismswap::pair::SimulationResponse;
+use prismswap::router::{Cw20HookMsg as RouterCw20HookMsg, SwapOperation};
 use prismswap::token::InstantiateMsg as TokenInstantiateMsg;
 
 use crate::math::decimal_division;
@@ -29,7 +37,7 @@ use crate::state::{
     all_unbond_history, get_finished_amount, get_unbond_batches, get_unbond_requests,
     is_valid_validator, query_get_finished_amount, read_unbond_history, read_unbond_wait_list,
     read_validators, remove_unbond_wait_list, remove_white_validators, store_unbond_history,
-    store_unbond_wait_list, store_white_validators, Parameters, UnbondHistory, CONFIG,
+    store_unbond_wait_list, store_white_validators, Parameters, UnbondHistory, CONFIG, DELEGATIONS,
 };
 use prism_common::testing::mock_querier::{mock_dependencies as dependencies, WasmMockQuerier};
 use prism_protocol::airdrop_registry::ExecuteMsg::FabricateClaim;
@@ -88,6 +96,8 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         token_admin: "admin0000".to_string(),
         token_code_id: 6u64,
         manager: "manager0000".to_string(),
+        underlying_symbol: None,
+        rebalance_cap: None,
     };
 
     let owner_info = mock_info(
@@ -126,6 +136,9 @@ pub fn init<S: Storage, A: Api, Q: Querier>(
         yluna_staking: Some(yluna_staking.to_string()),
         airdrop_registry_contract: Some("airdrop_registry".to_string()),
         manager: None,
+        prismswap_router: None,
+        guardian: None,
+        collector: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), owner_info, register_msg).unwrap();
@@ -190,6 +203,8 @@ fn proper_initialization() {
         token_admin: "admin0000".to_string(),
         token_code_id: 3u64,
         manager: "manager0000".to_string(),
+        underlying_symbol: None,
+        rebalance_cap: None,
     };
 
     let init_amt = 1_000_000;
@@ -381,6 +396,7 @@ fn proper_initialization() {
     assert_eq!(query_params.unbonding_period, 210);
     assert_eq!(query_params.peg_recovery_fee, Decimal::zero());
     assert_eq!(query_params.er_threshold, Decimal::one());
+    assert_eq!(query_params.rebalance_cap, Decimal::percent(10));
 
     // state storage must be initialized
     let state = QueryMsg::State {};
@@ -410,6 +426,10 @@ fn proper_initialization() {
         airdrop_registry_contract: "".to_string(),
         initialized: false,
         manager: "manager0000".to_string(),
+        prismswap_router: None,
+        guardian: None,
+        underlying_symbol: "Luna".to_string(),
+        collector: None,
     };
 
     assert_eq!(expected_conf, query_conf);
@@ -422,11 +442,68 @@ fn proper_initialization() {
         query_batch,
         CurrentBatchResponse {
             id: 1,
-            requested_with_fee: Default::default()
+            requested_with_fee: Default::default(),
+            keeper_reward: Default::default(),
         }
     );
 }
 
+/// Covers naming the tokens after another underlying asset.
+#[test]
+fn proper_initialization_underlying_symbol() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let msg = InstantiateMsg {
+        epoch_period: 30,
+        underlying_coin_denom: "uatom".to_string(),
+        unbonding_period: 210,
+        peg_recovery_fee: Decimal::zero(),
+        er_threshold: Decimal::one(),
+        validator: validator.address,
+        token_admin: "admin0000".to_string(),
+        token_code_id: 3u64,
+        manager: "manager0000".to_string(),
+        underlying_symbol: Some("Atom".to_string()),
+        rebalance_cap: None,
+    };
+    let owner_info = mock_info(OWNER, &[coin(1000000, "uatom")]);
+    let res = instantiate(deps.as_mut(), mock_env(), owner_info, msg).unwrap();
+    match &res.messages[2].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => {
+            let token_msg: TokenInstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(token_msg.name, "Prism cAtom Token");
+            assert_eq!(token_msg.symbol, "cAtom");
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let reply_msg = Reply {
+        id: 0,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(vec![10, 5, 99, 108, 117, 110, 97].into()),
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { msg, .. }) => {
+            let token_msg: TokenInstantiateMsg = from_binary(msg).unwrap();
+            assert_eq!(token_msg.name, "Prism pAtom Token");
+            assert_eq!(token_msg.symbol, "pAtom");
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.underlying_symbol, "Atom");
+    let params: ParametersResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Parameters {}).unwrap()).unwrap();
+    assert_eq!(params.underlying_coin_denom, "uatom");
+}
+
 /// Covers if a given validator is registered in whitelisted validator storage.
 #[test]
 fn proper_register_validator() {
@@ -710,14 +787,15 @@ fn proper_pick_bond_validator() {
 
     let bond_msg = ExecuteMsg::Bond { validator: None };
 
-    // first bond will go to the first validator it will be directly selected since it has the lowest delegation
+    // first bond goes to the second validator, since the initial deposit is
+    // recorded as a delegation to the first one
     let info = mock_info(addr1.as_str(), &[coin(bond_amount.u128(), "uluna")]);
     let res = execute(deps.as_mut(), mock_env(), info.clone(), bond_msg.clone()).unwrap();
     assert_eq!(
         res.messages,
         vec![
             SubMsg::new(CosmosMsg::Staking(StakingMsg::Delegate {
-                validator: validator.address.clone(),
+                validator: validator2.address.clone(),
                 amount: Coin {
                     denom: UNDERLYING_COIN_DENOM.to_string(),
                     amount: bond_amount,
@@ -747,10 +825,16 @@ fn proper_pick_bond_validator() {
     deps.querier.update_staking(
         "uluna",
         &[validator.clone(), validator2.clone()],
-        &[sample_delegation(
-            validator.address.clone(),
-            coin(INITIAL_DEPOSIT_AMOUNT.u128() + bond_amount.u128(), "uluna"),
-        )],
+        &[
+            sample_delegation(
+                validator.address.clone(),
+                coin(INITIAL_DEPOSIT_AMOUNT.u128(), "uluna"),
+            ),
+            sample_delegation(
+                validator2.address.clone(),
+                coin(bond_amount.u128(), "uluna"),
+            ),
+        ],
     );
 
     // now second validator should be selected
@@ -791,16 +875,19 @@ fn proper_pick_bond_validator() {
     deps.querier.update_staking(
         "uluna",
         &[validator.clone(), validator2.clone()],
-        &[sample_delegation(
-            validator.address,
-            coin(
-                INITIAL_DEPOSIT_AMOUNT.u128() + bond_amount.u128() + bond_amount_2.u128(),
-                "uluna",
+        &[
+            sample_delegation(
+                validator.address,
+                coin(INITIAL_DEPOSIT_AMOUNT.u128(), "uluna"),
             ),
-        )],
+            sample_delegation(
+                validator2.address.clone(),
+                coin(bond_amount.u128() + bond_amount_2.u128(), "uluna"),
+            ),
+        ],
     );
 
-    // now validator 2 should be selcteed again, because bond_amount_2 < bond_amount
+    // validator 2 is still furthest below its target share
     let info = mock_info(addr1.as_str(), &[coin(bond_amount_2.u128(), "uluna")]);
     let res = execute(deps.as_mut(), mock_env(), info.clone(), bond_msg).unwrap();
     assert_eq!(
@@ -826,6 +913,126 @@ fn proper_pick_bond_validator() {
     );
 }
 
+/// Covers setting validator weights, splitting a bond towards the target
+/// weights and the ValidatorWeights query.
+#[test]
+fn proper_validator_weights() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+
+    let weights_msg = ExecuteMsg::UpdateValidatorWeights {
+        weights: vec![
+            ValidatorWeight {
+                validator: validator.address.clone(),
+                weight: 1,
+            },
+            ValidatorWeight {
+                validator: validator2.address.clone(),
+                weight: 3,
+            },
+        ],
+    };
+
+    // only the owner can set weights
+    let info = mock_info("addr1000", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, weights_msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    // validator must be whitelisted
+    let info = mock_info(OWNER, &[]);
+    let msg = ExecuteMsg::UpdateValidatorWeights {
+        weights: vec![ValidatorWeight {
+            validator: DEFAULT_VALIDATOR3.to_string(),
+            weight: 1,
+        }],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "Validator {} is not whitelisted",
+            DEFAULT_VALIDATOR3
+        ))
+    );
+
+    let info = mock_info(OWNER, &[]);
+    execute(deps.as_mut(), mock_env(), info, weights_msg).unwrap();
+
+    let delegations: [FullDelegation; 2] = [
+        sample_delegation(validator.address.clone(), coin(1000, "uluna")),
+        sample_delegation(validator2.address.clone(), coin(1000, "uluna")),
+    ];
+    let validators: [Validator; 2] = [validator.clone(), validator2.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(2000))],
+    )]);
+
+    let res: ValidatorWeightsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ValidatorWeights {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        ValidatorWeightsResponse {
+            total_delegated: Uint128::new(2000),
+            validators: vec![
+                ValidatorWeightResponse {
+                    validator: validator.address.clone(),
+                    weight: 1,
+                    delegated: Uint128::new(1000),
+                    target_share: Decimal::percent(25),
+                    actual_share: Decimal::percent(50),
+                },
+                ValidatorWeightResponse {
+                    validator: validator2.address.clone(),
+                    weight: 3,
+                    delegated: Uint128::new(1000),
+                    target_share: Decimal::percent(75),
+                    actual_share: Decimal::percent(50),
+                },
+            ],
+        }
+    );
+
+    // targets after the bond are 2000 and 6000, so the bond is split 1000/5000
+    let info = mock_info("addr1000", &[coin(6000, "uluna")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Bond { validator: None },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Delegate {
+            validator: validator.address,
+            amount: coin(1000, "uluna"),
+        }))
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Delegate {
+            validator: validator2.address,
+            amount: coin(5000, "uluna"),
+        }))
+    );
+}
+
 #[test]
 fn proper_bond_split() {
     let mut deps = dependencies(&[]);
@@ -961,6 +1168,46 @@ fn proper_split() {
             })),
         ]
     );
+
+    // cLuna sent to the vault is split for the recipient
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: addr1.clone(),
+        amount: bond_amount,
+        msg: to_binary(&Split {
+            recipient: Some("zap0000".to_string()),
+        })
+        .unwrap(),
+    });
+
+    let info = mock_info(YLUNA_CONTRACT, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, receive.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, receive).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: YLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "zap0000".to_string(),
+                    amount: bond_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: PLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "zap0000".to_string(),
+                    amount: bond_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
 }
 
 #[test]
@@ -977,6 +1224,7 @@ fn proper_merge() {
 
     let split_msg = ExecuteMsg::Merge {
         amount: bond_amount,
+        recipient: None,
     };
 
     let info = mock_info(addr1.as_str(), &[]);
@@ -1014,6 +1262,27 @@ fn proper_merge() {
             })),
         ]
     );
+
+    // the cLuna can be paid to another recipient
+    let merge_msg = ExecuteMsg::Merge {
+        amount: bond_amount,
+        recipient: Some("addr2000".to_string()),
+    };
+    let info = mock_info(addr1.as_str(), &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, merge_msg).unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: CLUNA_CONTRACT.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr2000".to_string(),
+                amount: bond_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
 }
 
 /// Covers if the Redelegate message and UpdateGlobalIndex are sent.
@@ -1682,6 +1951,7 @@ pub fn proper_unbond() {
     let all_batches = AllHistory {
         start_from: None,
         limit: None,
+        released: None,
     };
     let res: AllHistoryResponse =
         from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
@@ -1691,21 +1961,10 @@ pub fn proper_unbond() {
     assert_eq!(res.history[0].batch_id, 1);
 }
 
-/// Covers if the pick_validator function sends different Undelegate messages
-/// to different validators, when a validator does not have enough delegation.
 #[test]
-pub fn proper_pick_validator() {
+pub fn proper_instant_unbond() {
     let mut deps = dependencies(&[]);
-
-    let addr1 = "addr1000".to_string();
-    let addr2 = "addr2000".to_string();
-    let addr3 = "addr3000".to_string();
-
-    // create 3 validators
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
-    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
-    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
-
     set_validator_mock(&mut deps.querier);
 
     init(
@@ -1714,163 +1973,155 @@ pub fn proper_pick_validator() {
         YLUNA_STAKING,
         validator.address.clone(),
     );
+    do_register_validator(deps.as_mut(), validator);
 
-    do_register_validator(deps.as_mut(), validator.clone());
-    do_register_validator(deps.as_mut(), validator2.clone());
-    do_register_validator(deps.as_mut(), validator3.clone());
+    let bob = "bob".to_string();
+    let token_info = mock_info(CLUNA_CONTRACT, &[]);
+    let instant_unbond = |min_receive: u128| {
+        Receive(Cw20ReceiveMsg {
+            sender: bob.clone(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&InstantUnbond {
+                min_receive: Uint128::new(min_receive),
+            })
+            .unwrap(),
+        })
+    };
 
-    // bond to a validator
-    do_bond(
-        deps.as_mut(),
-        addr1.clone(),
-        Uint128::new(10),
-        validator.clone(),
-    );
-    do_bond(
-        deps.as_mut(),
-        addr2.clone(),
-        Uint128::new(300),
-        validator2.clone(),
-    );
-    do_bond(
+    // the router is not set yet
+    let err = execute(
         deps.as_mut(),
-        addr3.clone(),
-        Uint128::new(200),
-        validator3.clone(),
-    );
+        mock_env(),
+        token_info.clone(),
+        instant_unbond(990),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Instant unbond is not enabled"));
 
-    // give validators different delegation amount
-    let delegations: [FullDelegation; 3] = [
-        (sample_delegation(validator.address.clone(), coin(10, "uluna"))),
-        (sample_delegation(validator2.address.clone(), coin(300, "uluna"))),
-        (sample_delegation(validator3.address.clone(), coin(200, "uluna"))),
-    ];
+    let update_config = UpdateConfig {
+        owner: None,
+        yluna_staking: None,
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: Some("router0000".to_string()),
+        guardian: None,
+        collector: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_config,
+    )
+    .unwrap();
 
-    let validators: [Validator; 3] = [
-        (validator.clone()),
-        (validator2.clone()),
-        (validator3.clone()),
-    ];
-    set_delegation_query(&mut deps.querier, &delegations, &validators);
-    deps.querier.with_token_balances(&[(
-        &"cluna".to_string(),
-        &[
-            (&addr3, &Uint128::new(200)),
-            (&addr2, &Uint128::new(300)),
-            (&addr1, &Uint128::new(10)),
-        ],
-    )]);
+    deps.querier.with_prismswap_sim_response(
+        "router0000",
+        &AssetInfo::Cw20(Addr::unchecked(CLUNA_CONTRACT)),
+        SimulationResponse {
+            return_amount: Uint128::new(990),
+            spread_amount: Uint128::new(7),
+            commission_amount: Uint128::new(3),
+        },
+    );
 
-    // send the first burn
-    let token_info = mock_info(CLUNA_CONTRACT, &[]);
-    let mut token_env = mock_env();
+    // only the cluna contract can send the hook
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("invalid", &[]),
+        instant_unbond(990),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
 
-    let res = do_unbond(
+    // the swap would return less than min_receive
+    let err = execute(
         deps.as_mut(),
-        addr2.clone(),
-        token_env.clone(),
+        mock_env(),
         token_info.clone(),
-        Uint128::new(50),
+        instant_unbond(991),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Instant unbond returns 990 which is less than min_receive 991")
     );
-    assert_eq!(res.messages.len(), 1);
-
-    deps.querier.with_token_balances(&[(
-        &"cluna".to_string(),
-        &[
-            (&addr3, &Uint128::new(200)),
-            (&addr2, &Uint128::new(250)),
-            (&addr1, &Uint128::new(10)),
-        ],
-    )]);
 
-    token_env.block.time = token_env.block.time.plus_seconds(40);
-
-    // send the second burn
-    let res = do_unbond(
+    let res = execute(
         deps.as_mut(),
-        addr2.clone(),
-        token_env,
-        token_info,
-        Uint128::new(100),
-    );
-    assert!(res.messages.len() >= 2);
-
-    deps.querier.with_token_balances(&[(
-        &"cluna".to_string(),
-        &[
-            (&addr3, &Uint128::new(200)),
-            (&addr2, &Uint128::new(150)),
-            (&addr1, &Uint128::new(10)),
-        ],
-    )]);
+        mock_env(),
+        token_info.clone(),
+        instant_unbond(990),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: CLUNA_CONTRACT.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "router0000".to_string(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                    operations: vec![SwapOperation::PrismSwap {
+                        offer_asset_info: AssetInfo::Cw20(Addr::unchecked(CLUNA_CONTRACT)),
+                        ask_asset_info: AssetInfo::Native("uluna".to_string()),
+                    }],
+                    minimum_receive: Some(Uint128::new(990)),
+                    to: Some(Addr::unchecked(&bob)),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "instant_unbond"),
+            attr("from", &bob),
+            attr("offer_amount", "1000"),
+            attr("estimated_return", "990"),
+            attr("standard_return", "1000"),
+            attr("estimated_discount", "0.01"),
+        ]
+    );
 
-    //check if the undelegate message is send two more than one validator.
-    if res.messages.len() > 2 {
-        match &res.messages[0].msg {
-            CosmosMsg::Staking(StakingMsg::Undelegate {
-                validator: val,
-                amount,
-            }) => {
-                if val == &validator.address {
-                    assert_eq!(amount.amount, Uint128::new(10))
-                }
-                if val == &validator2.address {
-                    assert_eq!(amount.amount, Uint128::new(150))
-                }
-                if val == &validator3.address {
-                    assert_eq!(amount.amount, Uint128::new(150))
-                }
-            }
-            _ => panic!("Unexpected message: {:?}", &res.messages[1]),
-        }
+    // nothing is queued for the standard unbond
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CurrentBatch {}).unwrap()).unwrap();
+    assert_eq!(query_batch.requested_with_fee, Uint128::zero());
 
-        match &res.messages[1].msg {
-            CosmosMsg::Staking(StakingMsg::Undelegate {
-                validator: val,
-                amount,
-            }) => {
-                if val == &validator2.address {
-                    assert_eq!(amount.amount, Uint128::new(140))
-                }
-                if val == &validator3.address {
-                    assert_eq!(amount.amount, Uint128::new(140))
-                }
-            }
-            _ => panic!("Unexpected message: {:?}", &res.messages[2]),
-        }
-    } else {
-        match &res.messages[1].msg {
-            CosmosMsg::Staking(StakingMsg::Undelegate {
-                validator: val,
-                amount,
-            }) => {
-                if val == &validator2.address {
-                    assert_eq!(amount.amount, Uint128::new(150))
-                }
-                if val == &validator3.address {
-                    assert_eq!(amount.amount, Uint128::new(150))
-                }
-            }
-            _ => panic!("Unexpected message: {:?}", &res.messages[1]),
-        }
-    }
+    // removing the router disables instant unbonds again
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&bob, &[]),
+        ExecuteMsg::RemovePrismswapRouter {},
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RemovePrismswapRouter {},
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.prismswap_router, None);
+
+    let err = execute(deps.as_mut(), mock_env(), token_info, instant_unbond(990)).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Instant unbond is not enabled"));
 }
 
-/// Covers if the pick_validator function sends different Undelegate messages
-/// if the delegations of the user are distributed to several validators
-/// and if the user wants to unbond amount that none of validators has.
 #[test]
-pub fn proper_pick_validator_respect_distributed_delegation() {
+pub fn proper_cancel_unbond() {
     let mut deps = dependencies(&[]);
-
-    let addr1 = "addr1000".to_string();
-    let addr2 = "addr2000".to_string();
-
-    // create 3 validators
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
-    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
-    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
-
     set_validator_mock(&mut deps.querier);
 
     init(
@@ -1879,244 +2130,310 @@ pub fn proper_pick_validator_respect_distributed_delegation() {
         YLUNA_STAKING,
         validator.address.clone(),
     );
-
     do_register_validator(deps.as_mut(), validator.clone());
-    do_register_validator(deps.as_mut(), validator2.clone());
-    do_register_validator(deps.as_mut(), validator3);
 
-    // bond to a validator
+    let bob = "bob".to_string();
     do_bond(
         deps.as_mut(),
-        addr1.clone(),
-        Uint128::new(1000),
+        bob.clone(),
+        Uint128::new(10),
         validator.clone(),
     );
-    do_bond(
+    set_delegation(&mut deps.querier, validator, 10, "uluna");
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(6u128))])]);
+
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: bob.clone(),
+        amount: Uint128::new(4),
+        msg: to_binary(&Unbond {}).unwrap(),
+    });
+    execute(
         deps.as_mut(),
-        addr1.clone(),
-        Uint128::new(1500),
-        validator2.clone(),
-    );
+        mock_env(),
+        mock_info(CLUNA_CONTRACT, &[]),
+        receive,
+    )
+    .unwrap();
 
-    // give validators different delegation amount
-    let delegations: [FullDelegation; 2] = [
-        (sample_delegation(validator.address.clone(), coin(1000, "uluna"))),
-        (sample_delegation(validator2.address.clone(), coin(1500, "uluna"))),
-    ];
+    let bob_info = mock_info(&bob, &[]);
 
-    let validators: [Validator; 2] = [(validator.clone()), (validator2.clone())];
-    set_delegation_query(&mut deps.querier, &delegations, &validators);
+    // only the current batch can be cancelled
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        bob_info.clone(),
+        ExecuteMsg::CancelUnbond {
+            batch_id: 2,
+            amount: Uint128::new(1),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Unbond requests of batch 2 can not be cancelled anymore")
+    );
 
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(2500))])]);
+    // can not cancel more than requested
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        bob_info.clone(),
+        ExecuteMsg::CancelUnbond {
+            batch_id: 1,
+            amount: Uint128::new(5),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Cancel amount 5 is more than the requested amount 4")
+    );
 
-    // send the first burn
-    let token_info = mock_info(CLUNA_CONTRACT, &[]);
+    // partial cancel
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        bob_info.clone(),
+        ExecuteMsg::CancelUnbond {
+            batch_id: 1,
+            amount: Uint128::new(1),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: CLUNA_CONTRACT.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: bob.clone(),
+                amount: Uint128::new(1),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(7u128))])]);
 
-    let mut token_env = mock_env();
-    token_env.block.time = token_env.block.time.plus_seconds(40);
+    assert_eq!(
+        read_unbond_wait_list(&deps.storage, 1, &Addr::unchecked(&bob)).unwrap(),
+        Uint128::new(3)
+    );
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CurrentBatch {}).unwrap()).unwrap();
+    assert_eq!(query_batch.requested_with_fee, Uint128::new(3));
 
-    let res = do_unbond(
+    // cancel the rest
+    execute(
         deps.as_mut(),
-        addr2,
-        token_env,
-        token_info,
-        Uint128::new(2000),
-    );
-    assert_eq!(res.messages.len(), 3);
+        mock_env(),
+        bob_info.clone(),
+        ExecuteMsg::CancelUnbond {
+            batch_id: 1,
+            amount: Uint128::new(3),
+        },
+    )
+    .unwrap();
+    let res: UnbondRequestsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            UnbondRequests {
+                address: bob.clone(),
+                start_from: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.requests.is_empty());
 
-    //check if the undelegate message is send two more than one validator.
-    if res.messages.len() > 2 {
-        match &res.messages[0].msg {
-            CosmosMsg::Staking(StakingMsg::Undelegate {
-                validator: val,
-                amount,
-            }) => {
-                if val == &validator.address {
-                    assert_eq!(amount.amount, Uint128::new(1000))
-                }
-                if val == &validator2.address {
-                    assert_eq!(amount.amount, Uint128::new(1500))
-                }
-            }
-            _ => panic!("Unexpected message: {:?}", &res.messages[1]),
-        }
+    let query_state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
+    assert_eq!(query_state.exchange_rate, Decimal::one());
 
-        match &res.messages[1].msg {
-            CosmosMsg::Staking(StakingMsg::Undelegate {
-                validator: val,
-                amount,
-            }) => {
-                if val == &validator.address {
-                    assert_eq!(amount.amount, Uint128::new(500))
-                }
-                if val == &validator2.address {
-                    assert_eq!(amount.amount, Uint128::new(1000))
-                }
-            }
-            _ => panic!("Unexpected message: {:?}", &res.messages[2]),
-        }
-    }
+    // once the batch is undelegated it can not be cancelled anymore
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(8u128))])]);
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: bob.clone(),
+        amount: Uint128::new(2),
+        msg: to_binary(&Unbond {}).unwrap(),
+    });
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    execute(deps.as_mut(), env, mock_info(CLUNA_CONTRACT, &[]), receive).unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        bob_info,
+        ExecuteMsg::CancelUnbond {
+            batch_id: 1,
+            amount: Uint128::new(2),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Unbond requests of batch 1 can not be cancelled anymore")
+    );
 }
 
-/// Covers the effect of slashing of bond, unbond, and withdraw_unbonded
-/// update the exchange rate after and before slashing.
 #[test]
-pub fn proper_slashing() {
+pub fn proper_transfer_unbond_claim() {
     let mut deps = dependencies(&[]);
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
     set_validator_mock(&mut deps.querier);
 
-    let addr1 = "addr1000".to_string();
-
     init(
         deps.borrow_mut(),
         OWNER,
         YLUNA_STAKING,
         validator.address.clone(),
     );
-
-    // The bond call won't accept a validator that isn't registered.
     do_register_validator(deps.as_mut(), validator.clone());
 
+    let bob = "bob".to_string();
+    let alice = "alice".to_string();
     do_bond(
         deps.as_mut(),
-        addr1.clone(),
-        Uint128::new(1000),
+        bob.clone(),
+        Uint128::new(10),
         validator.clone(),
     );
+    set_delegation(&mut deps.querier, validator, 10, "uluna");
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[(&bob, &Uint128::new(6u128)), (&alice, &Uint128::new(2u128))],
+    )]);
 
-    //this will set the balance of the user in token contract
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1000u128))])]);
-
-    // slashing
-    set_delegation(&mut deps.querier, validator.clone(), 900, "uluna");
-
-    let info = mock_info(&addr1, &[]);
-    let report_slashing = CheckSlashing {};
-    let res = execute(deps.as_mut(), mock_env(), info, report_slashing).unwrap();
-    assert_eq!(0, res.messages.len());
-
-    let ex_rate = QueryMsg::State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    assert_eq!(query_exchange_rate.exchange_rate.to_string(), "0.9");
+    for (sender, amount) in [(&bob, 2u128), (&alice, 2u128)] {
+        let receive = Receive(Cw20ReceiveMsg {
+            sender: sender.clone(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&Unbond {}).unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(CLUNA_CONTRACT, &[]),
+            receive,
+        )
+        .unwrap();
+    }
 
-    // bond again to see the update exchange rate
-    let second_bond = ExecuteMsg::Bond {
-        validator: Some(validator.address.clone()),
+    let transfer = |recipient: &str| ExecuteMsg::TransferUnbondClaim {
+        batch_id: 1,
+        recipient: recipient.to_string(),
     };
 
-    let info = mock_info(&addr1, &[coin(1000, "uluna")]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&bob, &[]),
+        transfer(&bob),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Can not transfer a claim to yourself")
+    );
 
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), second_bond).unwrap();
-    assert_eq!(2, res.messages.len());
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("carol", &[]),
+        transfer(&bob),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("No unbond claim found for batch 1")
+    );
 
-    // expected exchange rate must be more than 0.9
-    let expected_er = Decimal::from_ratio(Uint128::new(1900), Uint128::new(2111));
-    let ex_rate = QueryMsg::State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    assert_eq!(query_exchange_rate.exchange_rate, expected_er);
+    // the claim is merged with the existing request of alice
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&bob, &[]),
+        transfer(&alice),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "transfer_unbond_claim"),
+            attr("from", &bob),
+            attr("to", &alice),
+            attr("batch_id", "1"),
+            attr("amount", "2"),
+        ]
+    );
 
-    let delegate = &res.messages[0].msg;
-    match delegate {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
-            assert_eq!(amount, &coin(1000, "uluna"));
+    let claim_query = |address: &str| QueryMsg::UnbondClaim {
+        address: address.to_string(),
+        batch_id: 1,
+    };
+    let res: UnbondClaimResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), claim_query(&alice)).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        UnbondClaimResponse {
+            address: alice.clone(),
+            batch_id: 1,
+            amount: Uint128::new(4),
+            released: false,
         }
-        _ => panic!("Unexpected message: {:?}", delegate),
-    }
+    );
+    let err = query(deps.as_ref(), mock_env(), claim_query(&bob)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("No unbond claim found for batch 1")
+    );
 
-    let message = &res.messages[1].msg;
-    match message {
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr,
-            msg,
-            funds: _,
-        }) => {
-            assert_eq!(contract_addr, CLUNA_CONTRACT);
-            assert_eq!(
-                msg,
-                &to_binary(&Cw20ExecuteMsg::Mint {
-                    recipient: info.sender.to_string(),
-                    amount: Uint128::new(1111)
-                })
-                .unwrap()
-            );
-        }
-        _ => panic!("Unexpected message: {:?}", message),
-    }
-
-    set_delegation(&mut deps.querier, validator.clone(), 100900, "uluna");
-
-    // update user balance
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(2111u128))])]);
-
-    let info = mock_info(&addr1, &[]);
+    // the new owner can withdraw once the batch is released
     let mut env = mock_env();
-    let _res =
-        execute_unbond(deps.as_mut(), env.clone(), Uint128::new(500), addr1.clone()).unwrap();
-
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1611u128))])]);
-
     env.block.time = env.block.time.plus_seconds(31);
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: bob.clone(),
+        amount: Uint128::new(1),
+        msg: to_binary(&Unbond {}).unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(CLUNA_CONTRACT, &[]),
+        receive,
+    )
+    .unwrap();
 
-    let res = execute_unbond(deps.as_mut(), env.clone(), Uint128::new(500), addr1.clone()).unwrap();
-    let msgs: SubMsg = SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
-        validator: validator.address,
-        amount: coin(900, "uluna"),
-    }));
-    assert_eq!(res.messages[0], msgs);
-
+    env.block.time = env.block.time.plus_seconds(3);
     deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1111u128))])]);
-
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(900),
+        .with_native_balances(&[(MOCK_CONTRACT_ADDR.to_string(), coin(4, "uluna"))]);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&alice, &[]),
+        ExecuteMsg::WithdrawUnbonded {
+            owner: None,
+            recipient: None,
         },
-    )]);
-
-    let ex_rate = QueryMsg::State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    assert_eq!(query_exchange_rate.exchange_rate, expected_er);
-
-    env.block.time = env.block.time.plus_seconds(90);
-    // check withdrawUnbonded message
-    let withdraw_unbond_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(deps.as_mut(), env, info, withdraw_unbond_msg).unwrap();
-    assert_eq!(wdraw_unbonded_res.messages.len(), 1);
-
-    let ex_rate = QueryMsg::State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    assert_eq!(query_exchange_rate.exchange_rate, expected_er);
-
-    let sent_message = &wdraw_unbonded_res.messages[0].msg;
-    match sent_message {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, &addr1);
-            assert_eq!(amount[0].amount, Uint128::new(900))
-        }
-
-        _ => panic!("Unexpected message: {:?}", sent_message),
+    )
+    .unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => assert_eq!(to_address, &alice),
+        msg => panic!("Unexpected message: {:?}", msg),
     }
+    assert!(read_unbond_wait_list(&deps.storage, 1, &Addr::unchecked(&alice)).is_err());
 }
 
-/// Covers if the withdraw_rate function is updated before and after withdraw_unbonded,
-/// the finished amount is accurate, user requests are removed from the waitlist, and
-/// the BankMsg::Send is sent.
 #[test]
-pub fn proper_withdraw_unbonded() {
+pub fn proper_withdraw_unbonded_operator() {
     let mut deps = dependencies(&[]);
-
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
     set_validator_mock(&mut deps.querier);
 
@@ -2126,178 +2443,157 @@ pub fn proper_withdraw_unbonded() {
         YLUNA_STAKING,
         validator.address.clone(),
     );
-
-    // register_validator
     do_register_validator(deps.as_mut(), validator.clone());
 
     let bob = "bob".to_string();
-    let bond_msg = ExecuteMsg::Bond {
-        validator: Some(validator.address.clone()),
-    };
-
-    let info = mock_info(&bob, &[coin(100, "uluna")]);
-
-    //set bob's balance to 10 in token contract
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(100u128))])]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
+    let alice = "alice".to_string();
+    do_bond(
+        deps.as_mut(),
+        bob.clone(),
+        Uint128::new(10),
+        validator.clone(),
+    );
+    set_delegation(&mut deps.querier, validator, 10, "uluna");
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[(&bob, &Uint128::new(6u128)), (&alice, &Uint128::new(2u128))],
+    )]);
 
-    let delegate = &res.messages[0].msg;
-    match delegate {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
-            assert_eq!(amount, &coin(100, "uluna"));
-        }
-        _ => panic!("Unexpected message: {:?}", delegate),
+    // the second unbond sends the batch
+    let mut env = mock_env();
+    for (sender, seconds) in [(&alice, 0u64), (&bob, 31u64)] {
+        env.block.time = env.block.time.plus_seconds(seconds);
+        let receive = Receive(Cw20ReceiveMsg {
+            sender: sender.clone(),
+            amount: Uint128::new(2),
+            msg: to_binary(&Unbond {}).unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(CLUNA_CONTRACT, &[]),
+            receive,
+        )
+        .unwrap();
     }
-
-    set_delegation(&mut deps.querier, validator, 100, "uluna");
-
-    let res = execute_unbond(deps.as_mut(), mock_env(), Uint128::new(10), bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-
+    env.block.time = env.block.time.plus_seconds(3);
     deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(90u128))])]);
-
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(0),
-        },
-    )]);
+        .with_native_balances(&[(MOCK_CONTRACT_ADDR.to_string(), coin(4, "uluna"))]);
 
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
-    //set the block time 30 seconds from now.
-    env.block.time = env.block.time.plus_seconds(31);
+    let approve = ExecuteMsg::ApproveOperator {
+        operator: "integration".to_string(),
+        recipient: Some("treasury".to_string()),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&bob, &[]), approve).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "approve_operator"),
+            attr("owner", &bob),
+            attr("operator", "integration"),
+            attr("recipient", "treasury"),
+        ]
+    );
 
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(
+    let approval_query = QueryMsg::OperatorApproval {
+        owner: bob.clone(),
+        operator: "integration".to_string(),
+    };
+    let res: OperatorApprovalResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), approval_query.clone()).unwrap()).unwrap();
+    assert!(res.approved);
+    assert_eq!(res.recipient, Some("treasury".to_string()));
+
+    // only approved operators can withdraw for the owner
+    let withdraw_for_bob = |recipient: Option<String>| ExecuteMsg::WithdrawUnbonded {
+        owner: Some(bob.clone()),
+        recipient,
+    };
+    let err = execute(
         deps.as_mut(),
         env.clone(),
-        info.clone(),
-        wdraw_unbonded_msg.clone(),
-    );
+        mock_info("carol", &[]),
+        withdraw_for_bob(None),
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
 
-    // trigger undelegation message
-    assert!(wdraw_unbonded_res.is_err());
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("integration", &[]),
+        withdraw_for_bob(Some("integration".to_string())),
+    )
+    .unwrap_err();
     assert_eq!(
-        wdraw_unbonded_res.unwrap_err(),
-        StdError::generic_err("No withdrawable uluna assets are available yet")
+        err,
+        StdError::generic_err("Operators can not choose the recipient")
     );
 
-    let res = execute_unbond(deps.as_mut(), env.clone(), Uint128::new(10), bob.clone()).unwrap();
-    assert_eq!(res.messages.len(), 2);
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(80u128))])]);
-
-    //this query should be zero since the undelegated period is not passed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::new(0));
-
-    env.block.time = env.block.time.plus_seconds(91);
-
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(20),
-        },
-    )]);
-    //first query AllUnbondedRequests
-    let all_unbonded = UnbondRequests {
-        address: bob.clone(),
-        start_from: None,
-        limit: None,
-    };
-    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
-    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
-    assert_eq!(res.requests.len(), 1);
-    //the amount should be 10
-    assert_eq!(&res.address, &bob);
-    assert_eq!(res.requests[0].1, Uint128::new(20));
-    assert_eq!(res.requests[0].0, 1);
-
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
-    };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].amount, Uint128::new(20));
-    assert_eq!(res.history[0].batch_id, 1);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("integration", &[]),
+        withdraw_for_bob(None),
+    )
+    .unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => assert_eq!(to_address, "treasury"),
+        msg => panic!("Unexpected message: {:?}", msg),
+    }
+    assert_eq!(res.attributes[3], attr("owner", &bob));
+    assert_eq!(res.attributes[4], attr("recipient", "treasury"));
 
-    //check with query
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
+    // owners can name the recipient themselves
+    let withdraw = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: Some("alice-wallet".to_string()),
     };
-    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::new(20));
-
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
-
-    assert_eq!(success_res.messages.len(), 1);
-
-    let sent_message = &success_res.messages[0].msg;
-    match sent_message {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, &bob);
-            assert_eq!(amount[0].amount, Uint128::new(20))
+    let res = execute(deps.as_mut(), env.clone(), mock_info(&alice, &[]), withdraw).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => {
+            assert_eq!(to_address, "alice-wallet")
         }
-
-        _ => panic!("Unexpected message: {:?}", sent_message),
+        msg => panic!("Unexpected message: {:?}", msg),
     }
 
-    //it should be removed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
+    let revoke = ExecuteMsg::RevokeOperator {
+        operator: "integration".to_string(),
     };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::new(0));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&bob, &[]),
+        revoke.clone(),
+    )
+    .unwrap();
+    let res: OperatorApprovalResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), approval_query).unwrap()).unwrap();
+    assert!(!res.approved);
 
-    let waitlist = UnbondRequests {
-        address: bob.clone(),
-        start_from: None,
-        limit: None,
-    };
-    let query_unbond: UnbondRequestsResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), waitlist).unwrap()).unwrap();
+    let err = execute(deps.as_mut(), env, mock_info(&bob, &[]), revoke).unwrap_err();
     assert_eq!(
-        query_unbond,
-        UnbondRequestsResponse {
-            address: bob,
-            requests: vec![]
-        }
+        err,
+        StdError::generic_err("integration is not an approved operator")
     );
-
-    // because of one that we add for each batch
-    let state = QueryMsg::State {};
-    let state_query: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), state).unwrap()).unwrap();
-    assert_eq!(state_query.prev_vault_balance, Uint128::new(0));
-    assert_eq!(state_query.exchange_rate, Decimal::one());
 }
 
-/// Covers slashing during the unbonded period and its effect on the finished amount.
+/// Covers if the pick_validator function sends the Undelegate message to the
+/// validator that is the furthest above its target weight.
 #[test]
-pub fn proper_withdraw_unbonded_respect_slashing() {
+pub fn proper_pick_validator() {
     let mut deps = dependencies(&[]);
 
-    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
-    set_validator_mock(&mut deps.querier);
+    let addr1 = "addr1000".to_string();
+    let addr2 = "addr2000".to_string();
+    let addr3 = "addr3000".to_string();
 
-    let bond_amount = Uint128::new(10000);
-    let unbond_amount = Uint128::new(500);
+    // create 3 validators
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
+
+    set_validator_mock(&mut deps.querier);
 
     init(
         deps.borrow_mut(),
@@ -2306,145 +2602,122 @@ pub fn proper_withdraw_unbonded_respect_slashing() {
         validator.address.clone(),
     );
 
-    // register_validator
     do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+    do_register_validator(deps.as_mut(), validator3.clone());
 
-    let bob = "bob".to_string();
-    let bond_msg = ExecuteMsg::Bond {
-        validator: Some(validator.address.clone()),
-    };
-
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
-
-    //set bob's balance to 10 in token contract
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
-
-    let delegate = &res.messages[0].msg;
-    match delegate {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
-            assert_eq!(amount, &coin(bond_amount.u128(), "uluna"));
-        }
-        _ => panic!("Unexpected message: {:?}", delegate),
-    }
-
-    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uluna");
-
-    let res = execute_unbond(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9500))])]);
-
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(0),
-        },
-    )]);
-
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
-    //set the block time 30 seconds from now.
-
-    env.block.time = env.block.time.plus_seconds(31);
-
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(
+    // bond to a validator
+    do_bond(
         deps.as_mut(),
-        env.clone(),
-        info.clone(),
-        wdraw_unbonded_msg.clone(),
+        addr1.clone(),
+        Uint128::new(10),
+        validator.clone(),
     );
-    assert!(wdraw_unbonded_res.is_err());
-    assert_eq!(
-        wdraw_unbonded_res.unwrap_err(),
-        StdError::generic_err("No withdrawable uluna assets are available yet")
+    do_bond(
+        deps.as_mut(),
+        addr2.clone(),
+        Uint128::new(300),
+        validator2.clone(),
+    );
+    do_bond(
+        deps.as_mut(),
+        addr3.clone(),
+        Uint128::new(200),
+        validator3.clone(),
     );
 
-    // trigger undelegation message
-    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9000))])]);
-
-    //this query should be zero since the undelegated period is not passed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::new(0));
-
-    env.block.time = env.block.time.plus_seconds(91);
+    // give validators different delegation amount
+    let delegations: [FullDelegation; 3] = [
+        (sample_delegation(validator.address.clone(), coin(10, "uluna"))),
+        (sample_delegation(validator2.address.clone(), coin(300, "uluna"))),
+        (sample_delegation(validator3.address.clone(), coin(200, "uluna"))),
+    ];
 
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(900),
-        },
+    let validators: [Validator; 3] = [
+        (validator.clone()),
+        (validator2.clone()),
+        (validator3.clone()),
+    ];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[
+            (&addr3, &Uint128::new(200)),
+            (&addr2, &Uint128::new(300)),
+            (&addr1, &Uint128::new(10)),
+        ],
     )]);
 
-    //first query AllUnbondedRequests
-    let all_unbonded = UnbondRequests {
-        address: bob.clone(),
-        start_from: None,
-        limit: None,
-    };
-    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
-    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
-    assert_eq!(res.requests.len(), 1);
-    //the amount should be 10
-    assert_eq!(&res.address, &bob);
-    assert_eq!(res.requests[0].1, Uint128::new(1000));
-    assert_eq!(res.requests[0].0, 1);
+    // send the first burn
+    let token_info = mock_info(CLUNA_CONTRACT, &[]);
+    let mut token_env = mock_env();
 
-    //check with query
-    //this query does not reflect the actual withdrawable
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::new(1000));
+    let res = do_unbond(
+        deps.as_mut(),
+        addr2.clone(),
+        token_env.clone(),
+        token_info.clone(),
+        Uint128::new(50),
+    );
+    assert_eq!(res.messages.len(), 1);
 
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[
+            (&addr3, &Uint128::new(200)),
+            (&addr2, &Uint128::new(250)),
+            (&addr1, &Uint128::new(10)),
+        ],
+    )]);
 
-    assert_eq!(success_res.messages.len(), 1);
+    token_env.block.time = token_env.block.time.plus_seconds(40);
 
-    let sent_message = &success_res.messages[0].msg;
-    match sent_message {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, &bob);
-            assert_eq!(amount[0].amount, Uint128::new(899))
-        }
+    // send the second burn
+    let res = do_unbond(
+        deps.as_mut(),
+        addr2.clone(),
+        token_env,
+        token_info,
+        Uint128::new(100),
+    );
+    assert!(res.messages.len() >= 2);
 
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[
+            (&addr3, &Uint128::new(200)),
+            (&addr2, &Uint128::new(150)),
+            (&addr1, &Uint128::new(10)),
+        ],
+    )]);
 
-    // there should not be any result
-    let withdrawable = WithdrawableUnbonded { address: bob };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::new(0));
+    // the whole claim is taken from validator2, the most over-weight validator
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: validator2.address,
+            amount: coin(150, "uluna"),
+        }))
+    );
 }
 
-/// Covers withdraw_unbonded/inactivity in the system while there are slashing events.
+/// Covers if the pick_validator function sends different Undelegate messages
+/// if the delegations are distributed to several validators and the
+/// unbonded amount is bigger than the excess of the most over-weight validator.
 #[test]
-pub fn proper_withdraw_unbonded_respect_inactivity_slashing() {
+pub fn proper_pick_validator_respect_distributed_delegation() {
     let mut deps = dependencies(&[]);
 
+    let addr1 = "addr1000".to_string();
+    let addr2 = "addr2000".to_string();
+
+    // create 3 validators
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
-    set_validator_mock(&mut deps.querier);
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
 
-    let bond_amount = Uint128::new(10000);
-    let unbond_amount = Uint128::new(500);
+    set_validator_mock(&mut deps.querier);
 
     init(
         deps.borrow_mut(),
@@ -2453,180 +2726,217 @@ pub fn proper_withdraw_unbonded_respect_inactivity_slashing() {
         validator.address.clone(),
     );
 
-    // register_validator
     do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+    do_register_validator(deps.as_mut(), validator3);
 
-    let bob = "bob".to_string();
-    let bond_msg = ExecuteMsg::Bond {
-        validator: Some(validator.address.clone()),
-    };
+    // bond to a validator
+    do_bond(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        validator.clone(),
+    );
+    do_bond(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1500),
+        validator2.clone(),
+    );
 
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+    // give validators different delegation amount
+    let delegations: [FullDelegation; 2] = [
+        (sample_delegation(validator.address.clone(), coin(1000, "uluna"))),
+        (sample_delegation(validator2.address.clone(), coin(1500, "uluna"))),
+    ];
+
+    let validators: [Validator; 2] = [(validator.clone()), (validator2.clone())];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
 
-    //set bob's balance to 10 in token contract
     deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(2500))])]);
 
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
+    // send the first burn
+    let token_info = mock_info(CLUNA_CONTRACT, &[]);
 
-    let delegate = &res.messages[0].msg;
-    match delegate {
-        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
-            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
-            assert_eq!(amount, &coin(bond_amount.u128(), "uluna"));
-        }
-        _ => panic!("Unexpected message: {:?}", delegate),
-    }
-
-    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uluna");
-
-    let res = execute_unbond(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
+    let mut token_env = mock_env();
+    token_env.block.time = token_env.block.time.plus_seconds(40);
 
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9500))])]);
+    let res = do_unbond(
+        deps.as_mut(),
+        addr2,
+        token_env,
+        token_info,
+        Uint128::new(2000),
+    );
+    assert_eq!(res.messages.len(), 3);
 
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(0),
-        },
-    )]);
+    // validator2 is the most over-weight one, validator3 has no delegation at all
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: validator2.address,
+            amount: coin(1334, "uluna"),
+        }))
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: validator.address,
+            amount: coin(666, "uluna"),
+        }))
+    );
+}
 
-    let info = mock_info(&bob, &[]);
-    let mut env = mock_env();
-    //set the block time 30 seconds from now.
+/// Covers the effect of slashing of bond, unbond, and withdraw_unbonded
+/// update the exchange rate after and before slashing.
+#[test]
+pub fn proper_exchange_rate_history() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
 
-    let current_batch = QueryMsg::CurrentBatch {};
-    let query_batch: CurrentBatchResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
-    assert_eq!(query_batch.id, 1);
-    assert_eq!(query_batch.requested_with_fee, unbond_amount);
+    let addr1 = "addr1000".to_string();
 
-    env.block.time = env.block.time.plus_seconds(1000);
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let wdraw_unbonded_res = execute(
-        deps.as_mut(),
-        env.clone(),
-        info.clone(),
-        wdraw_unbonded_msg.clone(),
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
     );
-    assert!(wdraw_unbonded_res.is_err());
-    assert_eq!(
-        wdraw_unbonded_res.unwrap_err(),
-        StdError::generic_err("No withdrawable uluna assets are available yet")
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_bond(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        validator.clone(),
     );
-
-    // trigger undelegation message
-    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
     deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9000))])]);
+        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1000u128))])]);
+    set_delegation(&mut deps.querier, validator.clone(), 1000, "uluna");
 
-    let current_batch = QueryMsg::CurrentBatch {};
-    let query_batch: CurrentBatchResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
-    assert_eq!(query_batch.id, 2);
-    assert_eq!(query_batch.requested_with_fee, Uint128::zero());
+    let start = mock_env().block.time.seconds();
+    let mut env = mock_env();
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
 
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
+    // update global index
+    let update_global = ExecuteMsg::UpdateGlobalIndex {
+        airdrop_hooks: None,
     };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), env.clone(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].amount, Uint128::new(1000));
-    assert_eq!(res.history[0].withdraw_rate.to_string(), "1");
-    assert!(!res.history[0].released);
-    assert_eq!(res.history[0].batch_id, 1);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        update_global,
+    )
+    .unwrap();
 
-    //this query should be zero since the undelegated period is not passed
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
-    };
-    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::zero());
+    // slashing
+    set_delegation(&mut deps.querier, validator.clone(), 900, "uluna");
+    env.block.time = env.block.time.plus_seconds(10);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
 
-    env.block.time = env.block.time.plus_seconds(1091);
+    // batch processing
+    env.block.time = env.block.time.plus_seconds(30);
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: addr1.clone(),
+        amount: Uint128::new(100),
+        msg: to_binary(&Unbond {}).unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(CLUNA_CONTRACT, &[]),
+        receive,
+    )
+    .unwrap();
 
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(900),
+    // a second snapshot in the same block does not overwrite the first one
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        ExecuteMsg::UpdateGlobalIndex {
+            airdrop_hooks: None,
         },
-    )]);
-    //first query AllUnbondedRequests
-    let all_unbonded = UnbondRequests {
-        address: bob.clone(),
-        start_from: None,
-        limit: None,
-    };
-    let query_unbonded = query(deps.as_ref(), env.clone(), all_unbonded).unwrap();
-    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
-    assert_eq!(res.requests.len(), 1);
-    //the amount should be 10
-    assert_eq!(&res.address, &bob);
-    assert_eq!(res.requests[0].1, Uint128::new(1000));
-    assert_eq!(res.requests[0].0, 1);
+    )
+    .unwrap();
 
-    //check with query
-    //this query does not reflect the actual withdrawable
-    let withdrawable = WithdrawableUnbonded {
-        address: bob.clone(),
+    let history_query = |start_after: Option<u64>, end_before: Option<u64>, limit: Option<u32>| {
+        let res: ExchangeRateHistoryResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ExchangeRateHistory {
+                    start_after,
+                    end_before,
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.history
     };
-    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
-    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
-    assert_eq!(res.withdrawable, Uint128::new(1000));
-
-    let success_res = execute(deps.as_mut(), env.clone(), info, wdraw_unbonded_msg).unwrap();
-
-    assert_eq!(success_res.messages.len(), 1);
-
-    let sent_message = &success_res.messages[0].msg;
-    match sent_message {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, &bob);
-            assert_eq!(amount[0].amount, Uint128::new(899))
-        }
-
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
+    assert_eq!(
+        history_query(None, None, None),
+        vec![
+            ExchangeRateSnapshotResponse {
+                id: 1,
+                time: start,
+                exchange_rate: state.exchange_rate,
+                total_bond_amount: state.total_bond_amount,
+            },
+            ExchangeRateSnapshotResponse {
+                id: 2,
+                time: start + 10,
+                exchange_rate: Decimal::percent(90),
+                total_bond_amount: Uint128::new(900),
+            },
+            ExchangeRateSnapshotResponse {
+                id: 3,
+                time: start + 40,
+                exchange_rate: Decimal::percent(90),
+                total_bond_amount: Uint128::new(810),
+            },
+            ExchangeRateSnapshotResponse {
+                id: 4,
+                time: start + 40,
+                exchange_rate: Decimal::percent(90),
+                total_bond_amount: Uint128::new(810),
+            },
+        ]
+    );
 
-    // there should not be any result
-    let withdrawable = WithdrawableUnbonded { address: bob };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), env, withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::new(0));
+    // paginate by id
+    let history = history_query(Some(1), None, Some(1));
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].id, 2);
+    assert!(history_query(Some(4), None, None).is_empty());
 
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
-    };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].amount, Uint128::new(1000));
-    assert_eq!(res.history[0].applied_exchange_rate.to_string(), "1");
-    assert_eq!(res.history[0].withdraw_rate.to_string(), "0.899");
-    assert!(res.history[0].released);
-    assert_eq!(res.history[0].batch_id, 1);
+    let history = history_query(Some(1), Some(4), None);
+    assert_eq!(
+        history.iter().map(|s| s.id).collect::<Vec<u64>>(),
+        vec![2, 3]
+    );
+    assert!(history_query(None, Some(1), None).is_empty());
 }
 
-/// Covers if the signed integer works properly,
-/// the exception when a user sends rogue coin.
 #[test]
-pub fn proper_withdraw_unbond_with_dummies() {
+pub fn proper_slashing_events() {
     let mut deps = dependencies(&[]);
-
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
     set_validator_mock(&mut deps.querier);
 
-    let bond_amount = Uint128::new(10000);
-    let unbond_amount = Uint128::new(500);
+    let addr1 = "addr1000".to_string();
 
     init(
         deps.borrow_mut(),
@@ -2634,205 +2944,190 @@ pub fn proper_withdraw_unbond_with_dummies() {
         YLUNA_STAKING,
         validator.address.clone(),
     );
-
-    // register_validator
     do_register_validator(deps.as_mut(), validator.clone());
-
-    let bob = "bob".to_string();
-    let bond_msg = ExecuteMsg::Bond {
-        validator: Some(validator.address.clone()),
-    };
-
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
-
-    //set bob's balance to 10 in token contract
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
-
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
-
-    set_delegation(
-        &mut deps.querier,
-        validator.clone(),
-        bond_amount.u128(),
-        "uluna",
+    do_register_validator(deps.as_mut(), validator2.clone());
+    do_bond(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        validator2.clone(),
     );
-
-    let res = execute_unbond(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9500))])]);
-
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(0),
-        },
+    deps.querier.with_token_balances(&[(
+        &"cluna".to_string(),
+        &[(&addr1, &(INITIAL_DEPOSIT_AMOUNT + Uint128::new(1000)))],
     )]);
 
-    let mut env = mock_env();
-    let info = mock_info(&bob, &[]);
-    //set the block time 30 seconds from now.
+    // validator2 is slashed by 10%
+    let delegations: [FullDelegation; 2] = [
+        sample_delegation(
+            validator.address.clone(),
+            coin(INITIAL_DEPOSIT_AMOUNT.u128(), "uluna"),
+        ),
+        sample_delegation(validator2.address.clone(), coin(900, "uluna")),
+    ];
+    let validators: [Validator; 2] = [validator.clone(), validator2.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
 
-    env.block.time = env.block.time.plus_seconds(31);
-    // trigger undelegation message
-    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9000))])]);
-
-    // slashing
-    set_delegation(
-        &mut deps.querier,
-        validator,
-        bond_amount.u128() - 2000,
-        "uluna",
+    let state: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()).unwrap();
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(10);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
+    let rate_after = Decimal::from_ratio(
+        INITIAL_DEPOSIT_AMOUNT + Uint128::new(900),
+        INITIAL_DEPOSIT_AMOUNT + Uint128::new(1000),
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "check_slashing"),
+            attr("new_exchange_rate", rate_after.to_string()),
+            attr("slashing_event", "1"),
+            attr("slashed_validator", validator2.address.clone()),
+            attr("expected_delegation", "1000"),
+            attr("actual_delegation", "900"),
+        ]
     );
 
-    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(1, res.messages.len());
-
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(8500))])]);
-
-    env.block.time = env.block.time.plus_seconds(31);
-
-    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
-    assert_eq!(2, res.messages.len());
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(8000))])]);
-
-    // fabricate balance of the hub contract
-    deps.querier.with_native_balances(&[(
-        MOCK_CONTRACT_ADDR.to_string(),
-        Coin {
-            denom: "uluna".to_string(),
-            amount: Uint128::new(2200),
-        },
-    )]);
-
-    env.block.time = env.block.time.plus_seconds(120);
-    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {};
-    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
-
-    assert_eq!(success_res.messages.len(), 1);
-
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
+    // the expected delegations are resynced, the loss is only reported once
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes.len(), 2);
+
+    // stale expected delegations without a live one are dropped, not reported
+    let stale_validator = Addr::unchecked(DEFAULT_VALIDATOR3);
+    DELEGATIONS
+        .save(&mut deps.storage, &stale_validator, &Uint128::new(500))
+        .unwrap();
+    DELEGATIONS
+        .save(
+            &mut deps.storage,
+            &Addr::unchecked("zero-validator"),
+            &Uint128::zero(),
+        )
+        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&addr1, &[]),
+        CheckSlashing {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes.len(), 2);
+    assert!(DELEGATIONS
+        .may_load(&deps.storage, &stale_validator)
+        .unwrap()
+        .is_none());
+
+    let events_query = |start_after: Option<u64>| {
+        let res: SlashingEventsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::SlashingEvents {
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        res.events
     };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    assert_eq!(res.history[0].amount, Uint128::new(1000));
-    assert_eq!(res.history[0].withdraw_rate.to_string(), "1.164");
-    assert!(res.history[0].released);
-    assert_eq!(res.history[0].batch_id, 1);
-    assert_eq!(res.history[1].amount, Uint128::new(1000));
-    assert_eq!(res.history[1].withdraw_rate.to_string(), "1.033");
-    assert!(res.history[1].released);
-    assert_eq!(res.history[1].batch_id, 2);
-
-    let expected = (res.history[0].withdraw_rate * res.history[0].amount)
-        + res.history[1].withdraw_rate * res.history[1].amount;
-    let sent_message = &success_res.messages[0].msg;
-    match sent_message {
-        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
-            assert_eq!(to_address, &bob);
-            assert_eq!(amount[0].amount, expected)
-        }
-
-        _ => panic!("Unexpected message: {:?}", sent_message),
-    }
-
-    // there should not be any result
-    let withdrawable = WithdrawableUnbonded { address: bob };
-    let query_with: WithdrawableUnbondedResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
-    assert_eq!(query_with.withdrawable, Uint128::new(0));
+    assert_eq!(
+        events_query(None),
+        vec![SlashingEventResponse {
+            id: 1,
+            validator: validator2.address.clone(),
+            expected_delegation: Uint128::new(1000),
+            actual_delegation: Uint128::new(900),
+            time: env.block.time.seconds(),
+            rate_before: state.exchange_rate,
+            rate_after,
+        }]
+    );
+    assert!(events_query(Some(1)).is_empty());
 }
 
-/// Covers if the state/parameters storage is updated to the given value,
-/// who sends the message, and if
-/// RewardUpdateDenom message is sent to the reward contract
+/// Covers the Delegations query comparing the recorded delegations with
+/// the live ones.
 #[test]
-pub fn test_update_params() {
+pub fn proper_delegations_query() {
     let mut deps = dependencies(&[]);
-
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
     set_validator_mock(&mut deps.querier);
 
-    //test with no swap denom.
-    let update_prams = UpdateParams {
-        epoch_period: Some(20),
-        unbonding_period: None,
-        peg_recovery_fee: None,
-        er_threshold: None,
-    };
-
-    init(deps.borrow_mut(), OWNER, YLUNA_STAKING, validator.address);
-
-    let invalid_info = mock_info("invalid", &[]);
-    let res = execute(
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+    do_bond(
         deps.as_mut(),
-        mock_env(),
-        invalid_info,
-        update_prams.clone(),
+        "addr1000".to_string(),
+        Uint128::new(1000),
+        validator2.clone(),
     );
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
-    let creator_info = mock_info(OWNER, &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    let params: Parameters =
-        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Parameters {}).unwrap()).unwrap();
-    assert_eq!(params.epoch_period, 20);
-    assert_eq!(params.underlying_coin_denom, "uluna");
-    assert_eq!(params.unbonding_period, 2);
-    assert_eq!(params.peg_recovery_fee, Decimal::zero());
-    assert_eq!(params.er_threshold, Decimal::one());
-
-    //test with some swap_denom.
-    let update_prams = UpdateParams {
-        epoch_period: None,
-        unbonding_period: Some(3),
-        peg_recovery_fee: Some(Decimal::one()),
-        er_threshold: Some(Decimal::zero()),
-    };
 
-    //the result must be 1
-    let creator_info = mock_info(OWNER, &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    // validator2 is slashed, validator3 holds a delegation the vault did not record
+    let delegations: [FullDelegation; 3] = [
+        sample_delegation(
+            validator.address.clone(),
+            coin(INITIAL_DEPOSIT_AMOUNT.u128(), "uluna"),
+        ),
+        sample_delegation(validator2.address.clone(), coin(900, "uluna")),
+        sample_delegation(validator3.address.clone(), coin(500, "uluna")),
+    ];
+    let validators: [Validator; 3] = [validator.clone(), validator2.clone(), validator3.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
 
-    let params: Parameters =
-        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Parameters {}).unwrap()).unwrap();
-    assert_eq!(params.epoch_period, 20);
-    assert_eq!(params.underlying_coin_denom, "uluna");
-    assert_eq!(params.unbonding_period, 3);
-    assert_eq!(params.peg_recovery_fee, Decimal::one());
-    assert_eq!(params.er_threshold, Decimal::zero());
+    let res: DelegationsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Delegations {}).unwrap()).unwrap();
+    assert_eq!(
+        res.delegations,
+        vec![
+            DelegationResponse {
+                validator: validator.address.clone(),
+                expected: INITIAL_DEPOSIT_AMOUNT,
+                actual: INITIAL_DEPOSIT_AMOUNT,
+            },
+            DelegationResponse {
+                validator: validator2.address.clone(),
+                expected: Uint128::new(1000),
+                actual: Uint128::new(900),
+            },
+            DelegationResponse {
+                validator: validator3.address.clone(),
+                expected: Uint128::zero(),
+                actual: Uint128::new(500),
+            },
+        ]
+    );
 }
 
-/// Covers if peg recovery is applied (in "bond", "unbond",
-/// and "withdraw_unbonded" messages) in case of a slashing event
 #[test]
-pub fn proper_recovery_fee() {
+pub fn proper_slashing() {
     let mut deps = dependencies(&[]);
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
     set_validator_mock(&mut deps.querier);
 
-    let update_prams = UpdateParams {
-        epoch_period: None,
-        unbonding_period: None,
-        peg_recovery_fee: Some(Decimal::from_ratio(Uint128::new(1), Uint128::new(1000))),
-        er_threshold: Some(Decimal::from_ratio(Uint128::new(99), Uint128::new(100))),
-    };
-
-    let bond_amount = Uint128::new(1000000u128);
-    let unbond_amount = Uint128::new(100000u128);
+    let addr1 = "addr1000".to_string();
 
     init(
         deps.borrow_mut(),
@@ -2841,38 +3136,24 @@ pub fn proper_recovery_fee() {
         validator.address.clone(),
     );
 
-    let creator_info = mock_info(OWNER, &[]);
-    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
-
-    let get_params = QueryMsg::Parameters {};
-    let parmas: Parameters =
-        from_binary(&query(deps.as_ref(), mock_env(), get_params).unwrap()).unwrap();
-    assert_eq!(parmas.epoch_period, 30);
-    assert_eq!(parmas.underlying_coin_denom, "uluna");
-    assert_eq!(parmas.unbonding_period, 2);
-    assert_eq!(parmas.peg_recovery_fee.to_string(), "0.001");
-    assert_eq!(parmas.er_threshold.to_string(), "0.99");
-
-    // register_validator
+    // The bond call won't accept a validator that isn't registered.
     do_register_validator(deps.as_mut(), validator.clone());
 
-    let bob = "bob".to_string();
-    let bond_msg = ExecuteMsg::Bond {
-        validator: Some(validator.address.clone()),
-    };
+    do_bond(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        validator.clone(),
+    );
 
     //this will set the balance of the user in token contract
     deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
-
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
-
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), bond_msg).unwrap();
-    assert_eq!(2, res.messages.len());
+        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1000u128))])]);
 
-    set_delegation(&mut deps.querier, validator.clone(), 900000, "uluna");
+    // slashing
+    set_delegation(&mut deps.querier, validator.clone(), 900, "uluna");
 
+    let info = mock_info(&addr1, &[]);
     let report_slashing = CheckSlashing {};
     let res = execute(deps.as_mut(), mock_env(), info, report_slashing).unwrap();
     assert_eq!(0, res.messages.len());
@@ -2882,276 +3163,2252 @@ pub fn proper_recovery_fee() {
         from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
     assert_eq!(query_exchange_rate.exchange_rate.to_string(), "0.9");
 
-    //Bond again to see the applied result
-    let bob = "bob".to_string();
-    let bond_msg = ExecuteMsg::Bond {
+    // bond again to see the update exchange rate
+    let second_bond = ExecuteMsg::Bond {
         validator: Some(validator.address.clone()),
     };
 
-    deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+    let info = mock_info(&addr1, &[coin(1000, "uluna")]);
 
-    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), second_bond).unwrap();
+    assert_eq!(2, res.messages.len());
 
-    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
-    let mint_amount = decimal_division(
-        bond_amount,
-        Decimal::from_ratio(Uint128::new(9), Uint128::new(10)),
-    );
-    let max_peg_fee = mint_amount * parmas.peg_recovery_fee;
-    let required_peg_fee = ((bond_amount + mint_amount + Uint128::zero())
-        .checked_sub(Uint128::new(900000) + bond_amount))
-    .unwrap();
-    let peg_fee = Uint128::min(max_peg_fee, required_peg_fee);
-    let mint_amount_with_fee = (mint_amount.checked_sub(peg_fee)).unwrap();
+    // expected exchange rate must be more than 0.9
+    let expected_er = Decimal::from_ratio(Uint128::new(1900), Uint128::new(2111));
+    let ex_rate = QueryMsg::State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(query_exchange_rate.exchange_rate, expected_er);
 
-    let mint_msg = &res.messages[1].msg;
-    match mint_msg {
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: _,
-            msg,
-            funds: _,
-        }) => assert_eq!(
-            msg,
-            &to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: bob.clone(),
-                amount: mint_amount_with_fee
-            })
-            .unwrap()
-        ),
-        _ => panic!("Unexpected message: {:?}", mint_msg),
+    let delegate = &res.messages[0].msg;
+    match delegate {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
+            assert_eq!(amount, &coin(1000, "uluna"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
     }
 
-    // check unbond message
-    let unbond = Unbond {};
-    let receive = Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: unbond_amount,
-        msg: to_binary(&unbond).unwrap(),
-    });
+    let message = &res.messages[1].msg;
+    match message {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds: _,
+        }) => {
+            assert_eq!(contract_addr, CLUNA_CONTRACT);
+            assert_eq!(
+                msg,
+                &to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: info.sender.to_string(),
+                    amount: Uint128::new(1111)
+                })
+                .unwrap()
+            );
+        }
+        _ => panic!("Unexpected message: {:?}", message),
+    }
 
-    let new_balance = bond_amount.checked_sub(unbond_amount).unwrap();
+    set_delegation(&mut deps.querier, validator.clone(), 100900, "uluna");
 
-    let mut token_env = mock_env();
-    let token_info = mock_info(CLUNA_CONTRACT, &[]);
-    let res = execute(
-        deps.as_mut(),
-        token_env.clone(),
-        token_info.clone(),
-        receive,
-    )
-    .unwrap();
-    assert_eq!(1, res.messages.len());
+    // update user balance
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(2111u128))])]);
 
-    //check current batch
-    let bonded_with_fee =
-        unbond_amount * Decimal::from_ratio(Uint128::new(999), Uint128::new(1000));
-    let current_batch = QueryMsg::CurrentBatch {};
-    let query_batch: CurrentBatchResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
-    assert_eq!(query_batch.id, 1);
-    assert_eq!(query_batch.requested_with_fee, bonded_with_fee);
+    let info = mock_info(&addr1, &[]);
+    let mut env = mock_env();
+    let _res =
+        execute_unbond(deps.as_mut(), env.clone(), Uint128::new(500), addr1.clone()).unwrap();
 
     deps.querier
-        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &new_balance)])]);
-
-    token_env.block.time = token_env.block.time.plus_seconds(60);
+        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1611u128))])]);
 
-    let second_unbond = Unbond {};
-    let receive = Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: unbond_amount,
-        msg: to_binary(&second_unbond).unwrap(),
-    });
-    let res = execute(deps.as_mut(), token_env.clone(), token_info, receive).unwrap();
-    assert_eq!(2, res.messages.len());
+    env.block.time = env.block.time.plus_seconds(31);
 
-    let ex_rate = QueryMsg::State {};
-    let query_exchange_rate: StateResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
-    let new_exchange = query_exchange_rate.exchange_rate;
+    let res = execute_unbond(deps.as_mut(), env.clone(), Uint128::new(500), addr1.clone()).unwrap();
+    let msgs: SubMsg = SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
+        validator: validator.address,
+        amount: coin(900, "uluna"),
+    }));
+    assert_eq!(res.messages[0], msgs);
 
-    let expected = bonded_with_fee + bonded_with_fee;
-    let undelegate_message = &res.messages[0].msg;
-    match undelegate_message {
-        CosmosMsg::Staking(StakingMsg::Undelegate {
-            validator: val,
-            amount,
-        }) => {
-            assert_eq!(&validator.address, val);
-            assert_eq!(amount.amount, expected * new_exchange);
-        }
-        _ => panic!("Unexpected message: {:?}", mint_msg),
-    }
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&addr1, &Uint128::new(1111u128))])]);
 
-    //got slashed during unbonding
     deps.querier.with_native_balances(&[(
         MOCK_CONTRACT_ADDR.to_string(),
         Coin {
             denom: "uluna".to_string(),
-            amount: Uint128::new(161870),
+            amount: Uint128::new(900),
         },
     )]);
 
-    token_env.block.time = token_env.block.time.plus_seconds(90);
-    //check withdrawUnbonded message
-    let withdraw_unbond_msg = ExecuteMsg::WithdrawUnbonded {};
-    let info = mock_info("addr0000", &[]);
-    let wdraw_unbonded_res = execute(deps.as_mut(), token_env, info, withdraw_unbond_msg).unwrap();
+    let ex_rate = QueryMsg::State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(query_exchange_rate.exchange_rate, expected_er);
+
+    env.block.time = env.block.time.plus_seconds(90);
+    // check withdrawUnbonded message
+    let withdraw_unbond_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let wdraw_unbonded_res = execute(deps.as_mut(), env, info, withdraw_unbond_msg).unwrap();
     assert_eq!(wdraw_unbonded_res.messages.len(), 1);
 
+    let ex_rate = QueryMsg::State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(query_exchange_rate.exchange_rate, expected_er);
+
     let sent_message = &wdraw_unbonded_res.messages[0].msg;
-    let expected = ((expected
-        * new_exchange
-        * Decimal::from_ratio(Uint128::new(161870), expected * new_exchange))
-    .checked_sub(Uint128::new(1)))
-    .unwrap();
     match sent_message {
-        CosmosMsg::Bank(BankMsg::Send {
-            to_address: _,
-            amount,
-        }) => {
-            assert_eq!(amount[0].amount, expected);
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, &addr1);
+            assert_eq!(amount[0].amount, Uint128::new(900))
         }
 
         _ => panic!("Unexpected message: {:?}", sent_message),
     }
-
-    let all_batches = AllHistory {
-        start_from: None,
-        limit: None,
-    };
-    let res: AllHistoryResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
-    // amount should be 99 + 99 since we store the requested amount with peg fee applied.
-    assert_eq!(res.history[0].amount, bonded_with_fee + bonded_with_fee);
-    assert_eq!(res.history[0].applied_exchange_rate, new_exchange);
-    assert_eq!(
-        res.history[0].withdraw_rate,
-        Decimal::from_ratio(Uint128::new(161869), bonded_with_fee + bonded_with_fee)
-    );
-    assert!(res.history[0].released);
-    assert_eq!(res.history[0].batch_id, 1);
 }
 
-/// Covers if the storage affected by update_config are updated properly
+/// Covers if the withdraw_rate function is updated before and after withdraw_unbonded,
+/// the finished amount is accurate, user requests are removed from the waitlist, and
+/// the BankMsg::Send is sent.
 #[test]
-pub fn proper_update_config() {
+pub fn proper_withdraw_unbonded() {
     let mut deps = dependencies(&[]);
 
     let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
     set_validator_mock(&mut deps.querier);
 
-    let new_owner = "new_owner".to_string();
-    let invalid_owner = "invalid_owner".to_string();
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
 
-    let msg = InstantiateMsg {
-        epoch_period: 30,
-        underlying_coin_denom: "uluna".to_string(),
-        unbonding_period: 2,
-        peg_recovery_fee: Decimal::zero(),
-        er_threshold: Decimal::one(),
-        validator: validator.address,
-        token_admin: "admin0000".to_string(),
-        token_code_id: 0u64,
-        manager: "manager0000".to_string(),
+    // register_validator
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let bob = "bob".to_string();
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
     };
 
-    let owner_info = mock_info(OWNER, &[coin(1000000, "uluna")]);
-    instantiate(deps.as_mut(), mock_env(), owner_info, msg).unwrap();
+    let info = mock_info(&bob, &[coin(100, "uluna")]);
 
-    // only the owner can call this message
-    let update_config = UpdateConfig {
-        owner: Some(new_owner.clone()),
-        yluna_staking: None,
-        airdrop_registry_contract: None,
-        manager: None,
-    };
-    let info = mock_info(&invalid_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config);
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    //set bob's balance to 10 in token contract
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(100u128))])]);
 
-    // change the owner
-    let update_config = UpdateConfig {
-        owner: Some(new_owner.clone()),
-        yluna_staking: None,
-        airdrop_registry_contract: None,
-        manager: None,
-    };
-    let info = mock_info(OWNER, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
 
-    let config = CONFIG.load(&deps.storage).unwrap();
-    assert_eq!(new_owner, config.owner);
+    let delegate = &res.messages[0].msg;
+    match delegate {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
+            assert_eq!(amount, &coin(100, "uluna"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
 
-    // new owner can send the owner related messages
-    let update_prams = UpdateParams {
-        epoch_period: None,
-        unbonding_period: None,
-        peg_recovery_fee: None,
-        er_threshold: None,
-    };
+    set_delegation(&mut deps.querier, validator, 100, "uluna");
 
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    let res = execute_unbond(deps.as_mut(), mock_env(), Uint128::new(10), bob.clone()).unwrap();
+    assert_eq!(1, res.messages.len());
 
-    //previous owner cannot send this message
-    let update_prams = UpdateParams {
-        epoch_period: None,
-        unbonding_period: None,
-        peg_recovery_fee: None,
-        er_threshold: None,
-    };
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(90u128))])]);
 
-    let new_owner_info = mock_info(OWNER, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams);
-    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(0),
+        },
+    )]);
 
-    let update_config = UpdateConfig {
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+    //set the block time 30 seconds from now.
+    env.block.time = env.block.time.plus_seconds(31);
+
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
         owner: None,
-        yluna_staking: Some("new reward".to_string()),
-        airdrop_registry_contract: None,
-        manager: None,
+        recipient: None,
     };
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 1);
+    let wdraw_unbonded_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        wdraw_unbonded_msg.clone(),
+    );
 
-    let msg: SubMsg = SubMsg::new(CosmosMsg::Distribution(
-        DistributionMsg::SetWithdrawAddress {
-            address: "new reward".to_string(),
+    // trigger undelegation message
+    assert!(wdraw_unbonded_res.is_err());
+    assert_eq!(
+        wdraw_unbonded_res.unwrap_err(),
+        StdError::generic_err("No withdrawable uluna assets are available yet")
+    );
+
+    let res = execute_unbond(deps.as_mut(), env.clone(), Uint128::new(10), bob.clone()).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(80u128))])]);
+
+    //this query should be zero since the undelegated period is not passed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::new(0));
+
+    env.block.time = env.block.time.plus_seconds(91);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(20),
         },
-    ));
-    assert_eq!(msg, res.messages[0]);
+    )]);
+    //first query AllUnbondedRequests
+    let all_unbonded = UnbondRequests {
+        address: bob.clone(),
+        start_from: None,
+        limit: None,
+    };
+    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
+    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
+    assert_eq!(res.requests.len(), 1);
+    //the amount should be 10
+    assert_eq!(&res.address, &bob);
+    assert_eq!(res.requests[0].1, Uint128::new(20));
+    assert_eq!(res.requests[0].0, 1);
 
-    let config = QueryMsg::Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
-    assert_eq!(config_query.yluna_staking, "new reward".to_string());
-    // contract is not yet fully initialized
-    assert!(!config_query.initialized);
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+        released: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].amount, Uint128::new(20));
+    assert_eq!(res.history[0].batch_id, 1);
 
-    let update_config = UpdateConfig {
-        owner: None,
-        yluna_staking: None,
-        airdrop_registry_contract: Some("new airdrop".to_string()),
-        manager: None,
+    //check with query
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
     };
-    let new_owner_info = mock_info(&new_owner, &[]);
-    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
-    assert_eq!(res.messages.len(), 0);
+    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::new(20));
 
-    let config = QueryMsg::Config {};
-    let config_query: ConfigResponse =
-        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let sent_message = &success_res.messages[0].msg;
+    match sent_message {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, &bob);
+            assert_eq!(amount[0].amount, Uint128::new(20))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    //it should be removed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::new(0));
+
+    let waitlist = UnbondRequests {
+        address: bob.clone(),
+        start_from: None,
+        limit: None,
+    };
+    let query_unbond: UnbondRequestsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), waitlist).unwrap()).unwrap();
     assert_eq!(
-        config_query.airdrop_registry_contract,
-        "new airdrop".to_string()
+        query_unbond,
+        UnbondRequestsResponse {
+            address: bob,
+            requests: vec![]
+        }
     );
-    // now the contract is initialized because yluna_staking and airdrop contract has been set
-    assert!(config_query.initialized);
+
+    // because of one that we add for each batch
+    let state = QueryMsg::State {};
+    let state_query: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), state).unwrap()).unwrap();
+    assert_eq!(state_query.prev_vault_balance, Uint128::new(0));
+    assert_eq!(state_query.exchange_rate, Decimal::one());
+}
+
+/// Covers slashing during the unbonded period and its effect on the finished amount.
+#[test]
+pub fn proper_withdraw_unbonded_respect_slashing() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let bond_amount = Uint128::new(10000);
+    let unbond_amount = Uint128::new(500);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+
+    // register_validator
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let bob = "bob".to_string();
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+
+    //set bob's balance to 10 in token contract
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    let delegate = &res.messages[0].msg;
+    match delegate {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
+            assert_eq!(amount, &coin(bond_amount.u128(), "uluna"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
+
+    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uluna");
+
+    let res = execute_unbond(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(1, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9500))])]);
+
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(0),
+        },
+    )]);
+
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+    //set the block time 30 seconds from now.
+
+    env.block.time = env.block.time.plus_seconds(31);
+
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let wdraw_unbonded_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        wdraw_unbonded_msg.clone(),
+    );
+    assert!(wdraw_unbonded_res.is_err());
+    assert_eq!(
+        wdraw_unbonded_res.unwrap_err(),
+        StdError::generic_err("No withdrawable uluna assets are available yet")
+    );
+
+    // trigger undelegation message
+    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9000))])]);
+
+    //this query should be zero since the undelegated period is not passed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::new(0));
+
+    env.block.time = env.block.time.plus_seconds(91);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(900),
+        },
+    )]);
+
+    //first query AllUnbondedRequests
+    let all_unbonded = UnbondRequests {
+        address: bob.clone(),
+        start_from: None,
+        limit: None,
+    };
+    let query_unbonded = query(deps.as_ref(), mock_env(), all_unbonded).unwrap();
+    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
+    assert_eq!(res.requests.len(), 1);
+    //the amount should be 10
+    assert_eq!(&res.address, &bob);
+    assert_eq!(res.requests[0].1, Uint128::new(1000));
+    assert_eq!(res.requests[0].0, 1);
+
+    //check with query
+    //this query does not reflect the actual withdrawable
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::new(1000));
+
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let sent_message = &success_res.messages[0].msg;
+    match sent_message {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, &bob);
+            assert_eq!(amount[0].amount, Uint128::new(899))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // there should not be any result
+    let withdrawable = WithdrawableUnbonded { address: bob };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::new(0));
+}
+
+/// Covers withdraw_unbonded/inactivity in the system while there are slashing events.
+#[test]
+pub fn proper_withdraw_unbonded_respect_inactivity_slashing() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let bond_amount = Uint128::new(10000);
+    let unbond_amount = Uint128::new(500);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+
+    // register_validator
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let bob = "bob".to_string();
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+
+    //set bob's balance to 10 in token contract
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    let delegate = &res.messages[0].msg;
+    match delegate {
+        CosmosMsg::Staking(StakingMsg::Delegate { validator, amount }) => {
+            assert_eq!(validator.as_str(), DEFAULT_VALIDATOR.to_string());
+            assert_eq!(amount, &coin(bond_amount.u128(), "uluna"));
+        }
+        _ => panic!("Unexpected message: {:?}", delegate),
+    }
+
+    set_delegation(&mut deps.querier, validator, bond_amount.u128(), "uluna");
+
+    let res = execute_unbond(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(1, res.messages.len());
+
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9500))])]);
+
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(0),
+        },
+    )]);
+
+    let info = mock_info(&bob, &[]);
+    let mut env = mock_env();
+    //set the block time 30 seconds from now.
+
+    let current_batch = QueryMsg::CurrentBatch {};
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
+    assert_eq!(query_batch.id, 1);
+    assert_eq!(query_batch.requested_with_fee, unbond_amount);
+
+    env.block.time = env.block.time.plus_seconds(1000);
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let wdraw_unbonded_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        wdraw_unbonded_msg.clone(),
+    );
+    assert!(wdraw_unbonded_res.is_err());
+    assert_eq!(
+        wdraw_unbonded_res.unwrap_err(),
+        StdError::generic_err("No withdrawable uluna assets are available yet")
+    );
+
+    // trigger undelegation message
+    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9000))])]);
+
+    let current_batch = QueryMsg::CurrentBatch {};
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
+    assert_eq!(query_batch.id, 2);
+    assert_eq!(query_batch.requested_with_fee, Uint128::zero());
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+        released: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].amount, Uint128::new(1000));
+    assert_eq!(res.history[0].withdraw_rate.to_string(), "1");
+    assert!(!res.history[0].released);
+    assert_eq!(res.history[0].batch_id, 1);
+
+    //this query should be zero since the undelegated period is not passed
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), mock_env(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::zero());
+
+    env.block.time = env.block.time.plus_seconds(1091);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(900),
+        },
+    )]);
+    //first query AllUnbondedRequests
+    let all_unbonded = UnbondRequests {
+        address: bob.clone(),
+        start_from: None,
+        limit: None,
+    };
+    let query_unbonded = query(deps.as_ref(), env.clone(), all_unbonded).unwrap();
+    let res: UnbondRequestsResponse = from_binary(&query_unbonded).unwrap();
+    assert_eq!(res.requests.len(), 1);
+    //the amount should be 10
+    assert_eq!(&res.address, &bob);
+    assert_eq!(res.requests[0].1, Uint128::new(1000));
+    assert_eq!(res.requests[0].0, 1);
+
+    //check with query
+    //this query does not reflect the actual withdrawable
+    let withdrawable = WithdrawableUnbonded {
+        address: bob.clone(),
+    };
+    let query_with = query(deps.as_ref(), env.clone(), withdrawable).unwrap();
+    let res: WithdrawableUnbondedResponse = from_binary(&query_with).unwrap();
+    assert_eq!(res.withdrawable, Uint128::new(1000));
+
+    let success_res = execute(deps.as_mut(), env.clone(), info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let sent_message = &success_res.messages[0].msg;
+    match sent_message {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, &bob);
+            assert_eq!(amount[0].amount, Uint128::new(899))
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // there should not be any result
+    let withdrawable = WithdrawableUnbonded { address: bob };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), env, withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::new(0));
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+        released: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].amount, Uint128::new(1000));
+    assert_eq!(res.history[0].applied_exchange_rate.to_string(), "1");
+    assert_eq!(res.history[0].withdraw_rate.to_string(), "0.899");
+    assert!(res.history[0].released);
+    assert_eq!(res.history[0].batch_id, 1);
+}
+
+/// Covers if the signed integer works properly,
+/// the exception when a user sends rogue coin.
+#[test]
+pub fn proper_withdraw_unbond_with_dummies() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let bond_amount = Uint128::new(10000);
+    let unbond_amount = Uint128::new(500);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+
+    // register_validator
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let bob = "bob".to_string();
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+
+    //set bob's balance to 10 in token contract
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    set_delegation(
+        &mut deps.querier,
+        validator.clone(),
+        bond_amount.u128(),
+        "uluna",
+    );
+
+    let res = execute_unbond(deps.as_mut(), mock_env(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(1, res.messages.len());
+
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9500))])]);
+
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(0),
+        },
+    )]);
+
+    let mut env = mock_env();
+    let info = mock_info(&bob, &[]);
+    //set the block time 30 seconds from now.
+
+    env.block.time = env.block.time.plus_seconds(31);
+    // trigger undelegation message
+    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(9000))])]);
+
+    // slashing
+    set_delegation(
+        &mut deps.querier,
+        validator,
+        bond_amount.u128() - 2000,
+        "uluna",
+    );
+
+    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(1, res.messages.len());
+
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(8500))])]);
+
+    env.block.time = env.block.time.plus_seconds(31);
+
+    let res = execute_unbond(deps.as_mut(), env.clone(), unbond_amount, bob.clone()).unwrap();
+    assert_eq!(2, res.messages.len());
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &Uint128::new(8000))])]);
+
+    // fabricate balance of the hub contract
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(2200),
+        },
+    )]);
+
+    env.block.time = env.block.time.plus_seconds(120);
+    let wdraw_unbonded_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let success_res = execute(deps.as_mut(), env, info, wdraw_unbonded_msg).unwrap();
+
+    assert_eq!(success_res.messages.len(), 1);
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+        released: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    assert_eq!(res.history[0].amount, Uint128::new(1000));
+    assert_eq!(res.history[0].withdraw_rate.to_string(), "1.164");
+    assert!(res.history[0].released);
+    assert_eq!(res.history[0].batch_id, 1);
+    assert_eq!(res.history[1].amount, Uint128::new(1000));
+    assert_eq!(res.history[1].withdraw_rate.to_string(), "1.033");
+    assert!(res.history[1].released);
+    assert_eq!(res.history[1].batch_id, 2);
+
+    let expected = (res.history[0].withdraw_rate * res.history[0].amount)
+        + res.history[1].withdraw_rate * res.history[1].amount;
+    let sent_message = &success_res.messages[0].msg;
+    match sent_message {
+        CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+            assert_eq!(to_address, &bob);
+            assert_eq!(amount[0].amount, expected)
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    // there should not be any result
+    let withdrawable = WithdrawableUnbonded { address: bob };
+    let query_with: WithdrawableUnbondedResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), withdrawable).unwrap()).unwrap();
+    assert_eq!(query_with.withdrawable, Uint128::new(0));
+}
+
+/// Covers if the state/parameters storage is updated to the given value,
+/// who sends the message, and if
+/// RewardUpdateDenom message is sent to the reward contract
+#[test]
+pub fn test_update_params() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    //test with no swap denom.
+    let update_prams = UpdateParams {
+        epoch_period: Some(20),
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+
+    init(deps.borrow_mut(), OWNER, YLUNA_STAKING, validator.address);
+
+    let invalid_info = mock_info("invalid", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        invalid_info,
+        update_prams.clone(),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+    let creator_info = mock_info(OWNER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Parameters {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 20);
+    assert_eq!(params.underlying_coin_denom, "uluna");
+    assert_eq!(params.unbonding_period, 2);
+    assert_eq!(params.peg_recovery_fee, Decimal::zero());
+    assert_eq!(params.er_threshold, Decimal::one());
+
+    //test with some swap_denom.
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: Some(3),
+        peg_recovery_fee: Some(Decimal::one()),
+        er_threshold: Some(Decimal::zero()),
+        rebalance_cap: Some(Decimal::percent(5)),
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+
+    //the result must be 1
+    let creator_info = mock_info(OWNER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let params: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Parameters {}).unwrap()).unwrap();
+    assert_eq!(params.epoch_period, 20);
+    assert_eq!(params.underlying_coin_denom, "uluna");
+    assert_eq!(params.unbonding_period, 3);
+    assert_eq!(params.peg_recovery_fee, Decimal::one());
+    assert_eq!(params.er_threshold, Decimal::zero());
+    assert_eq!(params.rebalance_cap, Decimal::percent(5));
+}
+
+/// Covers if peg recovery is applied (in "bond", "unbond",
+/// and "withdraw_unbonded" messages) in case of a slashing event
+#[test]
+pub fn proper_recovery_fee() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: Some(Decimal::from_ratio(Uint128::new(1), Uint128::new(1000))),
+        er_threshold: Some(Decimal::from_ratio(Uint128::new(99), Uint128::new(100))),
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+
+    let bond_amount = Uint128::new(1000000u128);
+    let unbond_amount = Uint128::new(100000u128);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+
+    let creator_info = mock_info(OWNER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), creator_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let get_params = QueryMsg::Parameters {};
+    let parmas: Parameters =
+        from_binary(&query(deps.as_ref(), mock_env(), get_params).unwrap()).unwrap();
+    assert_eq!(parmas.epoch_period, 30);
+    assert_eq!(parmas.underlying_coin_denom, "uluna");
+    assert_eq!(parmas.unbonding_period, 2);
+    assert_eq!(parmas.peg_recovery_fee.to_string(), "0.001");
+    assert_eq!(parmas.er_threshold.to_string(), "0.99");
+
+    // register_validator
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let bob = "bob".to_string();
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+
+    //this will set the balance of the user in token contract
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), bond_msg).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    set_delegation(&mut deps.querier, validator.clone(), 900000, "uluna");
+
+    let report_slashing = CheckSlashing {};
+    let res = execute(deps.as_mut(), mock_env(), info, report_slashing).unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let ex_rate = QueryMsg::State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    assert_eq!(query_exchange_rate.exchange_rate.to_string(), "0.9");
+
+    //Bond again to see the applied result
+    let bob = "bob".to_string();
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &bond_amount)])]);
+
+    let info = mock_info(&bob, &[coin(bond_amount.u128(), "uluna")]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    let mint_amount = decimal_division(
+        bond_amount,
+        Decimal::from_ratio(Uint128::new(9), Uint128::new(10)),
+    );
+    let max_peg_fee = mint_amount * parmas.peg_recovery_fee;
+    let required_peg_fee = ((bond_amount + mint_amount + Uint128::zero())
+        .checked_sub(Uint128::new(900000) + bond_amount))
+    .unwrap();
+    let peg_fee = Uint128::min(max_peg_fee, required_peg_fee);
+    let mint_amount_with_fee = (mint_amount.checked_sub(peg_fee)).unwrap();
+
+    let mint_msg = &res.messages[1].msg;
+    match mint_msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: _,
+            msg,
+            funds: _,
+        }) => assert_eq!(
+            msg,
+            &to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: bob.clone(),
+                amount: mint_amount_with_fee
+            })
+            .unwrap()
+        ),
+        _ => panic!("Unexpected message: {:?}", mint_msg),
+    }
+
+    // check unbond message
+    let unbond = Unbond {};
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: unbond_amount,
+        msg: to_binary(&unbond).unwrap(),
+    });
+
+    let new_balance = bond_amount.checked_sub(unbond_amount).unwrap();
+
+    let mut token_env = mock_env();
+    let token_info = mock_info(CLUNA_CONTRACT, &[]);
+    let res = execute(
+        deps.as_mut(),
+        token_env.clone(),
+        token_info.clone(),
+        receive,
+    )
+    .unwrap();
+    assert_eq!(1, res.messages.len());
+
+    //check current batch
+    let bonded_with_fee =
+        unbond_amount * Decimal::from_ratio(Uint128::new(999), Uint128::new(1000));
+    let current_batch = QueryMsg::CurrentBatch {};
+    let query_batch: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), current_batch).unwrap()).unwrap();
+    assert_eq!(query_batch.id, 1);
+    assert_eq!(query_batch.requested_with_fee, bonded_with_fee);
+
+    deps.querier
+        .with_token_balances(&[(&"cluna".to_string(), &[(&bob, &new_balance)])]);
+
+    token_env.block.time = token_env.block.time.plus_seconds(60);
+
+    let second_unbond = Unbond {};
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: unbond_amount,
+        msg: to_binary(&second_unbond).unwrap(),
+    });
+    let res = execute(deps.as_mut(), token_env.clone(), token_info, receive).unwrap();
+    assert_eq!(2, res.messages.len());
+
+    let ex_rate = QueryMsg::State {};
+    let query_exchange_rate: StateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), ex_rate).unwrap()).unwrap();
+    let new_exchange = query_exchange_rate.exchange_rate;
+
+    let expected = bonded_with_fee + bonded_with_fee;
+    let undelegate_message = &res.messages[0].msg;
+    match undelegate_message {
+        CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: val,
+            amount,
+        }) => {
+            assert_eq!(&validator.address, val);
+            assert_eq!(amount.amount, expected * new_exchange);
+        }
+        _ => panic!("Unexpected message: {:?}", mint_msg),
+    }
+
+    //got slashed during unbonding
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::new(161870),
+        },
+    )]);
+
+    token_env.block.time = token_env.block.time.plus_seconds(90);
+    //check withdrawUnbonded message
+    let withdraw_unbond_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let wdraw_unbonded_res = execute(deps.as_mut(), token_env, info, withdraw_unbond_msg).unwrap();
+    assert_eq!(wdraw_unbonded_res.messages.len(), 1);
+
+    let sent_message = &wdraw_unbonded_res.messages[0].msg;
+    let expected = ((expected
+        * new_exchange
+        * Decimal::from_ratio(Uint128::new(161870), expected * new_exchange))
+    .checked_sub(Uint128::new(1)))
+    .unwrap();
+    match sent_message {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: _,
+            amount,
+        }) => {
+            assert_eq!(amount[0].amount, expected);
+        }
+
+        _ => panic!("Unexpected message: {:?}", sent_message),
+    }
+
+    let all_batches = AllHistory {
+        start_from: None,
+        limit: None,
+        released: None,
+    };
+    let res: AllHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), all_batches).unwrap()).unwrap();
+    // amount should be 99 + 99 since we store the requested amount with peg fee applied.
+    assert_eq!(res.history[0].amount, bonded_with_fee + bonded_with_fee);
+    assert_eq!(res.history[0].applied_exchange_rate, new_exchange);
+    assert_eq!(
+        res.history[0].withdraw_rate,
+        Decimal::from_ratio(Uint128::new(161869), bonded_with_fee + bonded_with_fee)
+    );
+    assert!(res.history[0].released);
+    assert_eq!(res.history[0].batch_id, 1);
+}
+
+/// Covers if the storage affected by update_config are updated properly
+#[test]
+pub fn proper_update_config() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let new_owner = "new_owner".to_string();
+    let invalid_owner = "invalid_owner".to_string();
+
+    let msg = InstantiateMsg {
+        epoch_period: 30,
+        underlying_coin_denom: "uluna".to_string(),
+        unbonding_period: 2,
+        peg_recovery_fee: Decimal::zero(),
+        er_threshold: Decimal::one(),
+        validator: validator.address,
+        token_admin: "admin0000".to_string(),
+        token_code_id: 0u64,
+        manager: "manager0000".to_string(),
+        underlying_symbol: None,
+        rebalance_cap: None,
+    };
+
+    let owner_info = mock_info(OWNER, &[coin(1000000, "uluna")]);
+    instantiate(deps.as_mut(), mock_env(), owner_info, msg).unwrap();
+
+    // only the owner can call this message
+    let update_config = UpdateConfig {
+        owner: Some(new_owner.clone()),
+        yluna_staking: None,
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: None,
+        guardian: None,
+        collector: None,
+    };
+    let info = mock_info(&invalid_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, update_config);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    // change the owner
+    let update_config = UpdateConfig {
+        owner: Some(new_owner.clone()),
+        yluna_staking: None,
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: None,
+        guardian: None,
+        collector: None,
+    };
+    let info = mock_info(OWNER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, update_config).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(new_owner, config.owner);
+
+    // new owner can send the owner related messages
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    //previous owner cannot send this message
+    let update_prams = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+
+    let new_owner_info = mock_info(OWNER, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_prams);
+    assert_eq!(res.unwrap_err(), StdError::generic_err("unauthorized"));
+
+    let update_config = UpdateConfig {
+        owner: None,
+        yluna_staking: Some("new reward".to_string()),
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: None,
+        guardian: None,
+        collector: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let msg: SubMsg = SubMsg::new(CosmosMsg::Distribution(
+        DistributionMsg::SetWithdrawAddress {
+            address: "new reward".to_string(),
+        },
+    ));
+    assert_eq!(msg, res.messages[0]);
+
+    let config = QueryMsg::Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    assert_eq!(config_query.yluna_staking, "new reward".to_string());
+    // contract is not yet fully initialized
+    assert!(!config_query.initialized);
+
+    let update_config = UpdateConfig {
+        owner: None,
+        yluna_staking: None,
+        airdrop_registry_contract: Some("new airdrop".to_string()),
+        manager: None,
+        prismswap_router: None,
+        guardian: None,
+        collector: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let res = execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let config = QueryMsg::Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    assert_eq!(
+        config_query.airdrop_registry_contract,
+        "new airdrop".to_string()
+    );
+    // now the contract is initialized because yluna_staking and airdrop contract has been set
+    assert!(config_query.initialized);
+
+    let update_config = UpdateConfig {
+        owner: None,
+        yluna_staking: None,
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: Some("router0000".to_string()),
+        guardian: None,
+        collector: None,
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    execute(deps.as_mut(), mock_env(), new_owner_info, update_config).unwrap();
+
+    let config = QueryMsg::Config {};
+    let config_query: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), config).unwrap()).unwrap();
+    assert_eq!(
+        config_query.prismswap_router,
+        Some("router0000".to_string())
+    );
+}
+
+/// Covers pausing and unpausing categories of operations by the guardian
+/// and the PauseStatus query.
+#[test]
+fn proper_pause_status() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let addr1 = "addr1000".to_string();
+    let guardian = "guardian0000".to_string();
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let pause_msg = ExecuteMsg::UpdatePauseStatus {
+        minting: Some(true),
+        refraction: None,
+        unbonding: Some(true),
+        withdrawals: Some(true),
+    };
+
+    // only the guardian or the owner can pause
+    let info = mock_info(&guardian, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, pause_msg.clone()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    let update_config = UpdateConfig {
+        owner: None,
+        yluna_staking: None,
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: None,
+        guardian: Some(guardian.clone()),
+        collector: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_config,
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.guardian, Some(guardian.clone()));
+
+    let info = mock_info(&guardian, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, pause_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_pause_status"),
+            attr("minting", "true"),
+            attr("refraction", "false"),
+            attr("unbonding", "true"),
+            attr("withdrawals", "true"),
+        ]
+    );
+
+    let status: PauseStatusResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+    assert_eq!(
+        status,
+        PauseStatusResponse {
+            minting: true,
+            refraction: false,
+            unbonding: true,
+            withdrawals: true,
+        }
+    );
+
+    // every paused handler is rejected
+    let info = mock_info(&addr1, &[coin(1000, "uluna")]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::Bond { validator: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Paused: minting"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::BondSplit { validator: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Paused: minting"));
+
+    let receive = Receive(Cw20ReceiveMsg {
+        sender: addr1.clone(),
+        amount: Uint128::new(10),
+        msg: to_binary(&Unbond {}).unwrap(),
+    });
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, receive).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Paused: unbonding"));
+
+    let withdraw = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let info = mock_info(&addr1, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, withdraw).unwrap_err();
+    assert_eq!(err, StdError::generic_err("Paused: withdrawals"));
+
+    // refraction was left running
+    let info = mock_info(&addr1, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::Split {
+            amount: Uint128::new(10),
+        },
+    )
+    .unwrap();
+
+    // the owner can unpause as well
+    let unpause_msg = ExecuteMsg::UpdatePauseStatus {
+        minting: Some(false),
+        refraction: Some(true),
+        unbonding: None,
+        withdrawals: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        unpause_msg,
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Merge {
+            amount: Uint128::new(10),
+            recipient: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Paused: refraction"));
+
+    do_bond(deps.as_mut(), addr1, Uint128::new(1000), validator);
+
+    // a removed guardian can not pause anymore
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&guardian, &[]),
+        ExecuteMsg::RemoveGuardian {},
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RemoveGuardian {},
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.guardian, None);
+
+    let pause_msg = ExecuteMsg::UpdatePauseStatus {
+        minting: Some(true),
+        refraction: None,
+        unbonding: None,
+        withdrawals: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&guardian, &[]),
+        pause_msg,
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+}
+
+/// Covers the mint and unbond fees sent to the collector as cLuna.
+#[test]
+fn proper_protocol_fees() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    let addr1 = "addr1000".to_string();
+    let collector = "collector0000".to_string();
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    deps.querier.with_token_balances(&[(
+        &CLUNA_CONTRACT.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &INITIAL_DEPOSIT_AMOUNT)],
+    )]);
+
+    let update_fees = |mint_fee: Decimal, unbond_fee: Decimal| UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: Some(mint_fee),
+        unbond_fee: Some(unbond_fee),
+        keeper_fee: None,
+    };
+
+    // fees need a collector
+    let info = mock_info(OWNER, &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_fees(Decimal::percent(1), Decimal::zero()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Protocol fee collector is not set")
+    );
+
+    let update_config = UpdateConfig {
+        owner: None,
+        yluna_staking: None,
+        airdrop_registry_contract: None,
+        manager: None,
+        prismswap_router: None,
+        guardian: None,
+        collector: Some(collector.clone()),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), update_config).unwrap();
+
+    // fees are capped
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_fees(Decimal::percent(10), Decimal::zero()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!("fee can not be greater than {}", MAX_PROTOCOL_FEE))
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        update_fees(Decimal::percent(1), Decimal::percent(2)),
+    )
+    .unwrap();
+    let params: ParametersResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Parameters {}).unwrap()).unwrap();
+    assert_eq!(params.mint_fee, Decimal::percent(1));
+    assert_eq!(params.unbond_fee, Decimal::percent(2));
+
+    // 1% of the minted cLuna goes to the collector
+    let bond_amount = Uint128::new(10000);
+    let info = mock_info(&addr1, &[coin(bond_amount.u128(), "uluna")]);
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), bond_msg).unwrap();
+    assert_eq!(
+        res.messages[1..],
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: CLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: collector.clone(),
+                    amount: Uint128::new(100),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: CLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: addr1.clone(),
+                    amount: Uint128::new(9900),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert!(res.attributes.contains(&attr("minted", "9900")));
+    assert!(res.attributes.contains(&attr("protocol_fee", "100")));
+
+    // bond split keeps the fee mint and splits the rest
+    let bond_split_msg = ExecuteMsg::BondSplit {
+        validator: Some(validator.address),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, bond_split_msg).unwrap();
+    assert_eq!(res.messages.len(), 5);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: CLUNA_CONTRACT.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: collector.clone(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert!(res.attributes.contains(&attr("protocol_fee", "100")));
+
+    // 2% of the unbonded cLuna is transferred to the collector, the rest is burnt
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    let res = do_unbond(deps.as_mut(), addr1, mock_env(), info, Uint128::new(1000));
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: CLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(980),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: CLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: collector,
+                    amount: Uint128::new(20),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert!(res.attributes.contains(&attr("protocol_fee", "20")));
+
+    // the collector can only be removed once the fees are zero
+    let info = mock_info(OWNER, &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::RemoveCollector {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Protocol fees must be zero to remove the collector")
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        update_fees(Decimal::zero(), Decimal::zero()),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::RemoveCollector {},
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("unauthorized"));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RemoveCollector {},
+    )
+    .unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(config.collector, None);
+}
+
+#[test]
+fn proper_simulations() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: Some(Decimal::percent(1)),
+        er_threshold: Some(Decimal::percent(99)),
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_params,
+    )
+    .unwrap();
+
+    let bob = "bob".to_string();
+    let res: SimulateBondResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateBond {
+                amount: Uint128::new(1000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SimulateBondResponse {
+            minted: Uint128::new(1000),
+            peg_recovery_fee: Uint128::zero(),
+            protocol_fee: Uint128::zero(),
+            exchange_rate: Decimal::one(),
+        }
+    );
+
+    do_bond(
+        deps.as_mut(),
+        bob.clone(),
+        Uint128::new(1000),
+        validator.clone(),
+    );
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(1000))])]);
+
+    // slashing is applied to the simulation without being reported first
+    set_delegation(
+        &mut deps.querier,
+        validator.clone(),
+        900,
+        UNDERLYING_COIN_DENOM,
+    );
+    let res: SimulateBondResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateBond {
+                amount: Uint128::new(900),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.exchange_rate, Decimal::percent(90));
+    assert_eq!(res.peg_recovery_fee, Uint128::new(10));
+    assert_eq!(res.minted, Uint128::new(990));
+
+    let info = mock_info(&bob, &[coin(900, UNDERLYING_COIN_DENOM)]);
+    let bond_msg = ExecuteMsg::Bond {
+        validator: Some(validator.address.clone()),
+    };
+    let exec_res = execute(deps.as_mut(), mock_env(), info, bond_msg).unwrap();
+    assert!(exec_res.attributes.contains(&attr("minted", "990")));
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(1990))])]);
+    set_delegation(&mut deps.querier, validator, 1800, UNDERLYING_COIN_DENOM);
+
+    // the first unbond does not undelegate the batch yet
+    let res: SimulateUnbondResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateUnbond {
+                amount: Uint128::new(1000),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let expected_release = mock_env().block.time.seconds() + 31 + 2;
+    assert_eq!(res.release_time, expected_release);
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    let exec_res = do_unbond(
+        deps.as_mut(),
+        bob.clone(),
+        mock_env(),
+        info.clone(),
+        Uint128::new(1000),
+    );
+    assert!(exec_res
+        .attributes
+        .contains(&attr("burnt_amount", res.burnt)));
+    assert!(exec_res
+        .attributes
+        .contains(&attr("unbonded_amount", res.burnt - res.peg_recovery_fee)));
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(990))])]);
+
+    let simulate_withdraw = QueryMsg::SimulateWithdraw {
+        address: bob.clone(),
+    };
+    let res: SimulateWithdrawResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), simulate_withdraw.clone()).unwrap()).unwrap();
+    assert_eq!(res.received, Uint128::zero());
+    assert!(!res.pending.is_zero());
+    assert_eq!(res.next_release_time, Some(expected_release));
+
+    // the unbond after the epoch period undelegates the batch
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    let res: SimulateUnbondResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateUnbond {
+                amount: Uint128::new(990),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.release_time, env.block.time.seconds() + 2);
+    let exec_res = do_unbond(
+        deps.as_mut(),
+        bob.clone(),
+        env.clone(),
+        info,
+        Uint128::new(990),
+    );
+    let undelegated = match &exec_res.messages[0].msg {
+        CosmosMsg::Staking(StakingMsg::Undelegate { amount, .. }) => amount.amount,
+        msg => panic!("Unexpected message: {:?}", msg),
+    };
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::zero())])]);
+
+    let res: SimulateWithdrawResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), simulate_withdraw.clone()).unwrap())
+            .unwrap();
+    assert_eq!(res.received, Uint128::zero());
+    assert_eq!(res.pending, undelegated);
+    assert_eq!(res.next_release_time, Some(env.block.time.seconds() + 2));
+
+    // the simulation matches the withdrawal once unbonded
+    env.block.time = env.block.time.plus_seconds(3);
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        coin(undelegated.u128(), UNDERLYING_COIN_DENOM),
+    )]);
+    let res: SimulateWithdrawResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), simulate_withdraw).unwrap()).unwrap();
+    assert_eq!(res.pending, Uint128::zero());
+    assert_eq!(res.next_release_time, None);
+    let withdraw_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    let exec_res = execute(deps.as_mut(), env, mock_info(&bob, &[]), withdraw_msg).unwrap();
+    assert_eq!(
+        exec_res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: bob,
+            amount: coins(res.received.u128(), UNDERLYING_COIN_DENOM),
+        }))]
+    );
+}
+
+#[test]
+fn proper_process_batch() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: Some(Decimal::percent(1)),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_params,
+    )
+    .unwrap();
+
+    let bob = "bob".to_string();
+    let keeper = "keeper0000".to_string();
+    do_bond(
+        deps.as_mut(),
+        bob.clone(),
+        Uint128::new(1000),
+        validator.clone(),
+    );
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(1000))])]);
+    set_delegation(&mut deps.querier, validator, 1000, UNDERLYING_COIN_DENOM);
+
+    // 1% of the unbonded cLuna is kept for the keeper
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    let res = do_unbond(
+        deps.as_mut(),
+        bob.clone(),
+        mock_env(),
+        info,
+        Uint128::new(100),
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: CLUNA_CONTRACT.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(99),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert!(res.attributes.contains(&attr("keeper_fee", "1")));
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(900))])]);
+
+    let res: NextBatchTimeResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::NextBatchTime {}).unwrap())
+            .unwrap();
+    let batch_time = mock_env().block.time.seconds() + 31;
+    assert_eq!(
+        res,
+        NextBatchTimeResponse {
+            batch_id: 1,
+            time: batch_time,
+            requested_with_fee: Uint128::new(99),
+            keeper_reward: Uint128::new(1),
+        }
+    );
+
+    // the epoch period has not passed yet
+    let info = mock_info(&keeper, &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ProcessBatch {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "The current batch can not be processed before {}",
+            batch_time
+        ))
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ProcessBatch {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
+                validator: DEFAULT_VALIDATOR.to_string(),
+                amount: coin(99, UNDERLYING_COIN_DENOM),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: CLUNA_CONTRACT.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: keeper.clone(),
+                    amount: Uint128::new(1),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert!(res.attributes.contains(&attr("batch_id", "1")));
+    assert!(res.attributes.contains(&attr("keeper_reward", "1")));
+
+    let history = read_unbond_history(&deps.storage, 1).unwrap();
+    assert_eq!(history.amount, Uint128::new(99));
+    assert_eq!(history.time, env.block.time.seconds());
+
+    let res: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::CurrentBatch {}).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        CurrentBatchResponse {
+            id: 2,
+            requested_with_fee: Uint128::zero(),
+            keeper_reward: Uint128::zero(),
+        }
+    );
+
+    // an empty batch is not processed
+    env.block.time = env.block.time.plus_seconds(31);
+    let err = execute(deps.as_mut(), env, info, ExecuteMsg::ProcessBatch {}).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The current batch has no unbond requests")
+    );
+}
+
+#[test]
+fn proper_cancel_unbond_with_keeper_fee() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: Some(Decimal::percent(1)),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_params,
+    )
+    .unwrap();
+
+    let bob = "bob".to_string();
+    do_bond(
+        deps.as_mut(),
+        bob.clone(),
+        Uint128::new(1000),
+        validator.clone(),
+    );
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(1000))])]);
+    set_delegation(&mut deps.querier, validator, 1000, UNDERLYING_COIN_DENOM);
+
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    let res = do_unbond(
+        deps.as_mut(),
+        bob.clone(),
+        mock_env(),
+        info,
+        Uint128::new(200),
+    );
+    assert!(res.attributes.contains(&attr("keeper_fee", "2")));
+    deps.querier.with_token_balances(&[(
+        &CLUNA_CONTRACT.to_string(),
+        &[
+            (&bob, &Uint128::new(800)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(2)),
+        ],
+    )]);
+
+    // the keeper fee of the cancelled share is refunded
+    for remaining_reward in [1u128, 0] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&bob, &[]),
+            ExecuteMsg::CancelUnbond {
+                batch_id: 1,
+                amount: Uint128::new(99),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: CLUNA_CONTRACT.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Mint {
+                        recipient: bob.clone(),
+                        amount: Uint128::new(99),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                })),
+                SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: CLUNA_CONTRACT.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: bob.clone(),
+                        amount: Uint128::new(1),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                })),
+            ]
+        );
+        assert!(res.attributes.contains(&attr("keeper_fee_refund", "1")));
+
+        let res: CurrentBatchResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CurrentBatch {}).unwrap())
+                .unwrap();
+        assert_eq!(res.keeper_reward, Uint128::new(remaining_reward));
+    }
+
+    let res: CurrentBatchResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CurrentBatch {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        CurrentBatchResponse {
+            id: 1,
+            requested_with_fee: Uint128::zero(),
+            keeper_reward: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn proper_user_unbond_status() {
+    let mut deps = dependencies(&[]);
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+
+    let bob = "bob".to_string();
+    do_bond(
+        deps.as_mut(),
+        bob.clone(),
+        Uint128::new(1000),
+        validator.clone(),
+    );
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(1000))])]);
+    set_delegation(&mut deps.querier, validator, 1000, UNDERLYING_COIN_DENOM);
+
+    let info = mock_info(CLUNA_CONTRACT, &[]);
+    do_unbond(
+        deps.as_mut(),
+        bob.clone(),
+        mock_env(),
+        info.clone(),
+        Uint128::new(100),
+    );
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(900))])]);
+
+    let user_status = QueryMsg::UserUnbondStatus {
+        address: bob.clone(),
+        start_from: None,
+        limit: None,
+    };
+    let res: UserUnbondStatusResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), user_status.clone()).unwrap()).unwrap();
+    let batch_time = mock_env().block.time.seconds() + 31;
+    assert_eq!(
+        res.requests,
+        vec![UnbondRequestStatusResponse {
+            batch_id: 1,
+            amount: Uint128::new(100),
+            batch_time: None,
+            release_time: batch_time + 2,
+            applied_exchange_rate: None,
+            withdraw_rate: None,
+            released: false,
+            claimable: false,
+        }]
+    );
+
+    // the second unbond undelegates the first batch and opens the next one
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    do_unbond(
+        deps.as_mut(),
+        bob.clone(),
+        env.clone(),
+        info,
+        Uint128::new(100),
+    );
+    deps.querier
+        .with_token_balances(&[(&CLUNA_CONTRACT.to_string(), &[(&bob, &Uint128::new(800))])]);
+
+    env.block.time = env.block.time.plus_seconds(3);
+    let res: UserUnbondStatusResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), user_status).unwrap()).unwrap();
+    assert_eq!(
+        res.requests,
+        vec![UnbondRequestStatusResponse {
+            batch_id: 1,
+            amount: Uint128::new(200),
+            batch_time: Some(batch_time),
+            release_time: batch_time + 2,
+            applied_exchange_rate: Some(Decimal::one()),
+            withdraw_rate: Some(Decimal::one()),
+            released: false,
+            claimable: true,
+        }]
+    );
+
+    // released batches can be left out of the history
+    let res: AllHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            AllHistory {
+                start_from: None,
+                limit: None,
+                released: Some(true),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.history.is_empty());
+
+    deps.querier.with_native_balances(&[(
+        MOCK_CONTRACT_ADDR.to_string(),
+        coin(200, UNDERLYING_COIN_DENOM),
+    )]);
+    let withdraw_msg = ExecuteMsg::WithdrawUnbonded {
+        owner: None,
+        recipient: None,
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(&bob, &[]),
+        withdraw_msg,
+    )
+    .unwrap();
+
+    let res: AllHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            AllHistory {
+                start_from: None,
+                limit: None,
+                released: Some(true),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.history.len(), 1);
+    assert!(res.history[0].released);
+    let res: AllHistoryResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            AllHistory {
+                start_from: None,
+                limit: None,
+                released: Some(false),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.history.is_empty());
 }
 
 #[test]
@@ -3428,7 +5685,7 @@ fn proper_unbond_storage() -> StdResult<()> {
     assert_eq!(res.len(), 0);
 
     //no unbond history yet
-    let res = all_unbond_history(deps.as_ref().storage, None, None)?;
+    let res = all_unbond_history(deps.as_ref().storage, None, None, None)?;
     assert_eq!(res.len(), 0);
 
     // no unbond_history for block 1
@@ -3466,7 +5723,7 @@ fn proper_unbond_storage() -> StdResult<()> {
     store_unbond_history(deps.as_mut().storage, history2.batch_id, history2.clone())?;
 
     // read all history
-    let res = all_unbond_history(deps.as_ref().storage, None, None)?;
+    let res = all_unbond_history(deps.as_ref().storage, None, None, None)?;
     assert_eq!(res.len(), 2);
     assert_eq!(res[0], history1);
     assert_eq!(res[1], history2);
@@ -3492,7 +5749,7 @@ fn proper_unbond_storage() -> StdResult<()> {
     let mut start: Option<u64> = None;
     let limit = Some(2u32);
     loop {
-        let res = all_unbond_history(deps.as_ref().storage, start, limit)?;
+        let res = all_unbond_history(deps.as_ref().storage, start, limit, None)?;
         if (res.len() as u32) < limit.unwrap() {
             break;
         }
@@ -3748,3 +6005,423 @@ fn test_redelegate() {
         StdError::generic_err("There is a redelegation in progress")
     );
 }
+
+#[test]
+fn proper_rebalance_default_cap() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+
+    let delegations: [FullDelegation; 1] = [sample_delegation(
+        validator.address.clone(),
+        coin(10000, "uluna"),
+    )];
+    let validators: [Validator; 2] = [validator.clone(), validator2.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+
+    // a fresh vault moves up to 10% of the total delegation per epoch
+    let info = mock_info("keeper0000", &[]);
+    let msg = ExecuteMsg::Rebalance { max_moves: None };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator: validator.address,
+            dst_validator: validator2.address,
+            amount: coin(1000, "uluna"),
+        }))
+    );
+
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The rebalance cap of the current epoch has been reached")
+    );
+}
+
+#[test]
+fn proper_rebalance() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+    do_register_validator(deps.as_mut(), validator3.clone());
+
+    let delegations: [FullDelegation; 2] = [
+        sample_delegation(validator.address.clone(), coin(9000, "uluna")),
+        sample_delegation(validator2.address.clone(), coin(3000, "uluna")),
+    ];
+    let validators: [Validator; 3] = [validator.clone(), validator2.clone(), validator3.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+
+    let info = mock_info("keeper0000", &[]);
+    let msg = ExecuteMsg::Rebalance { max_moves: None };
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: Some(Decimal::percent(50)),
+        commission_ceiling: None,
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_params,
+    )
+    .unwrap();
+
+    // targets are 4000 each, validator3 has the biggest deficit
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::Rebalance { max_moves: Some(1) },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator: validator.address.clone(),
+                dst_validator: validator3.address.clone(),
+                amount: coin(4000, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::UpdateGlobalIndex {
+                    airdrop_hooks: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "rebalance"),
+            attr("moves", "1"),
+            attr("redelegated_amount", "4000"),
+        ]
+    );
+
+    // only 2000 out of the 6000 epoch cap are left
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator: validator.address.clone(),
+            dst_validator: validator3.address.clone(),
+            amount: coin(2000, "uluna"),
+        }))
+    );
+
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("The rebalance cap of the current epoch has been reached")
+    );
+
+    // a new epoch starts, but validator3 stake was just redelegated and can not move again
+    let delegations: [FullDelegation; 3] = [
+        sample_delegation(validator.address, coin(2000, "uluna")),
+        sample_delegation(validator2.address, coin(2000, "uluna")),
+        FullDelegation {
+            validator: validator3.address,
+            delegator: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            amount: coin(8000, "uluna"),
+            can_redelegate: coin(0, "uluna"),
+            accumulated_rewards: vec![],
+        },
+    ];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(31);
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Delegations are already balanced")
+    );
+}
+
+#[test]
+fn proper_check_validators() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let mut validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    let validator3 = sample_validator(DEFAULT_VALIDATOR3.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+    do_register_validator(deps.as_mut(), validator3.clone());
+    do_bond(
+        deps.as_mut(),
+        "addr0000".to_string(),
+        Uint128::new(2000),
+        validator2.clone(),
+    );
+    do_bond(
+        deps.as_mut(),
+        "addr0000".to_string(),
+        Uint128::new(3000),
+        validator3.clone(),
+    );
+
+    // validator3 is jailed, validator2 raises its commission
+    validator2.commission = Decimal::percent(20);
+    let delegations: [FullDelegation; 3] = [
+        sample_delegation(
+            validator.address.clone(),
+            coin(INITIAL_DEPOSIT_AMOUNT.u128(), "uluna"),
+        ),
+        sample_delegation(validator2.address.clone(), coin(2000, "uluna")),
+        sample_delegation(validator3.address.clone(), coin(3000, "uluna")),
+    ];
+    let validators: [Validator; 2] = [validator.clone(), validator2.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+
+    // no commission ceiling yet, only validator3 is removed and its stake goes
+    // to validator2, the one furthest below its target
+    let info = mock_info("keeper0000", &[]);
+    let msg = ExecuteMsg::CheckValidators {};
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+                src_validator: validator3.address.clone(),
+                dst_validator: validator2.address.clone(),
+                amount: coin(3000, "uluna"),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::UpdateGlobalIndex {
+                    airdrop_hooks: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "check_validators"),
+            attr("deregistered_validator", validator3.address.clone()),
+            attr("reason", "jailed"),
+        ]
+    );
+
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: Some(Decimal::percent(10)),
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_params,
+    )
+    .unwrap();
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Staking(StakingMsg::Redelegate {
+            src_validator: validator2.address.clone(),
+            dst_validator: validator.address.clone(),
+            amount: coin(2000, "uluna"),
+        }))
+    );
+
+    let res: ValidatorStatusesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ValidatorStatuses {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.validators,
+        vec![
+            ValidatorStatusResponse {
+                validator: validator2.address.clone(),
+                reason: DeregistrationReason::HighCommission {
+                    commission: Decimal::percent(20),
+                },
+                time: mock_env().block.time.seconds(),
+            },
+            ValidatorStatusResponse {
+                validator: validator3.address.clone(),
+                reason: DeregistrationReason::Jailed {},
+                time: mock_env().block.time.seconds(),
+            },
+        ]
+    );
+
+    assert!(!is_valid_validator(&deps.storage, &Addr::unchecked(&validator2.address)).unwrap());
+    assert!(!is_valid_validator(&deps.storage, &Addr::unchecked(&validator3.address)).unwrap());
+
+    // nothing left to do
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // registering a validator again clears its status
+    do_register_validator(deps.as_mut(), validator2.clone());
+    let res: ValidatorStatusesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ValidatorStatuses {
+                start_after: Some(validator2.address.clone()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.validators.len(), 1);
+    assert_eq!(res.validators[0].validator, validator3.address);
+
+    // the last healthy validators can not be removed
+    let update_params = UpdateParams {
+        epoch_period: None,
+        unbonding_period: None,
+        peg_recovery_fee: None,
+        er_threshold: None,
+        rebalance_cap: None,
+        commission_ceiling: Some(Decimal::percent(1)),
+        mint_fee: None,
+        unbond_fee: None,
+        keeper_fee: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        update_params,
+    )
+    .unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("There are no healthy validators to redelegate to")
+    );
+}
+
+#[test]
+fn proper_check_validators_missing() {
+    let mut deps = dependencies(&[]);
+
+    let validator = sample_validator(DEFAULT_VALIDATOR.to_string());
+    let validator2 = sample_validator(DEFAULT_VALIDATOR2.to_string());
+    set_validator_mock(&mut deps.querier);
+
+    init(
+        deps.borrow_mut(),
+        OWNER,
+        YLUNA_STAKING,
+        validator.address.clone(),
+    );
+    do_register_validator(deps.as_mut(), validator.clone());
+    do_register_validator(deps.as_mut(), validator2.clone());
+
+    // validator2 leaves the validator set without any vault stake on it
+    let delegations: [FullDelegation; 1] = [sample_delegation(
+        validator.address.clone(),
+        coin(INITIAL_DEPOSIT_AMOUNT.u128(), "uluna"),
+    )];
+    let validators: [Validator; 1] = [validator.clone()];
+    set_delegation_query(&mut deps.querier, &delegations, &validators);
+
+    let info = mock_info("keeper0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CheckValidators {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "check_validators"),
+            attr("deregistered_validator", validator2.address.clone()),
+            attr("reason", "missing"),
+        ]
+    );
+
+    let res: ValidatorStatusesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ValidatorStatuses {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.validators,
+        vec![ValidatorStatusResponse {
+            validator: validator2.address.clone(),
+            reason: DeregistrationReason::Missing {},
+            time: mock_env().block.time.seconds(),
+        }]
+    );
+    assert!(!is_valid_validator(&deps.storage, &Addr::unchecked(&validator2.address)).unwrap());
+}
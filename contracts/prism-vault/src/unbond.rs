@@ -1,15 +1,22 @@
 use crate::contract::{query_total_issued, slashing};
+use crate::delegation::pick_unbond_validators;
 use crate::state::{
-    get_finished_amount, get_unbond_batches, read_unbond_history, remove_unbond_wait_list,
-    store_unbond_history, store_unbond_wait_list, State, UnbondHistory, CONFIG, CURRENT_BATCH,
-    PARAMETERS, STATE,
+    assert_not_paused, get_finished_amount, get_unbond_batches, read_unbond_history,
+    remove_unbond_wait_list, store_exchange_rate_snapshot, store_unbond_history,
+    store_unbond_keeper_fee, store_unbond_wait_list, sub_delegation, CurrentBatch,
+    OperatorApproval, Parameters, State, UnbondHistory, CONFIG, CURRENT_BATCH, OPERATORS,
+    PARAMETERS, STATE, UNBOND_KEEPER_FEES, UNBOND_WAITLIST,
 };
 use cosmwasm_std::{
-    attr, coin, coins, to_binary, BankMsg, CosmosMsg, Decimal, DepsMut, Env, MessageInfo,
-    QuerierWrapper, Response, StakingMsg, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+    attr, coin, coins, to_binary, Addr, BankMsg, CosmosMsg, Decimal, DepsMut, Env, MessageInfo,
+    Response, StakingMsg, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
-use rand::{Rng, SeedableRng, XorShiftRng};
+use cw_asset::AssetInfo;
+use prismswap::router::{
+    Cw20HookMsg as RouterCw20HookMsg, QueryMsg as RouterQueryMsg, SimulateSwapOperationsResponse,
+    SwapOperation,
+};
 use signed_integer::SignedInt;
 
 /// This message must be called by receive_cw20
@@ -20,28 +27,33 @@ pub(crate) fn execute_unbond(
     amount: Uint128,
     sender: String, // human who sent the c-luna to us
 ) -> StdResult<Response> {
+    assert_not_paused(deps.storage, |p| p.unbonding, "unbonding")?;
     let mut current_batch = CURRENT_BATCH.load(deps.storage)?;
 
     // Check slashing, update state, and calculate the new exchange rate.
     let params = PARAMETERS.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
-    slashing(&mut deps, env.clone(), &mut state, &params)?;
+    let slashing_attrs = slashing(&mut deps, env.clone(), &mut state, &params)?;
 
     let mut total_supply = query_total_issued(deps.as_ref()).unwrap_or_default();
 
     // Collect all the requests within a epoch period
-    // Apply peg recovery fee
-    let amount_with_fee = if state.exchange_rate < params.er_threshold {
-        let max_peg_fee = amount * params.peg_recovery_fee;
-        let required_peg_fee = ((total_supply + current_batch.requested_with_fee)
-            .checked_sub(state.total_bond_amount))?;
-        let peg_fee = Uint128::min(max_peg_fee, required_peg_fee);
-        (amount.checked_sub(peg_fee))?
-    } else {
-        amount
-    };
+    let UnbondAmounts {
+        burnt: amount,
+        requested: amount_with_fee,
+        protocol_fee,
+        keeper_fee,
+        ..
+    } = compute_unbond_amounts(
+        &state,
+        &params,
+        total_supply,
+        current_batch.requested_with_fee,
+        amount,
+    )?;
 
     current_batch.requested_with_fee += amount_with_fee;
+    current_batch.keeper_reward += keeper_fee;
 
     let sender_addr = deps.api.addr_validate(&sender)?;
     store_unbond_wait_list(
@@ -50,6 +62,7 @@ pub(crate) fn execute_unbond(
         &sender_addr,
         amount_with_fee,
     )?;
+    store_unbond_keeper_fee(deps.storage, current_batch.id, &sender_addr, keeper_fee)?;
 
     total_supply = (total_supply.checked_sub(amount))
         .expect("the requested can not be more than the total supply");
@@ -65,50 +78,18 @@ pub(crate) fn execute_unbond(
 
     let mut messages: Vec<SubMsg> = vec![];
 
-    // If the epoch period is passed, the undelegate message would be sent.
+    // If the epoch period is passed, the undelegate message would be sent
+    // and the sender gets the keeper reward of the batch.
     if passed_time_seconds > params.epoch_period {
-        // Apply the current exchange rate.
-        let undelegation_amount = current_batch.requested_with_fee * state.exchange_rate;
-
-        // the contract must stop if
-        if undelegation_amount == Uint128::new(1) {
-            return Err(StdError::generic_err(
-                "Burn amount must be greater than 1 micro unit",
-            ));
-        }
-
-        let delegator = env.contract.address;
-
-        // Send undelegated requests to possibly more than one validators
-        let mut undelegated_msgs = pick_unbond_validator(
-            &deps.querier,
-            params.underlying_coin_denom,
-            undelegation_amount,
-            delegator.to_string(),
-            env.block.height,
+        let (batch_messages, _) = process_batch(
+            deps.branch(),
+            &env,
+            &params,
+            &mut state,
+            &mut current_batch,
+            &sender_addr,
         )?;
-
-        messages.append(&mut undelegated_msgs);
-
-        state.total_bond_amount = (state.total_bond_amount.checked_sub(undelegation_amount))
-            .expect("undelegation amount can not be more than stored total bonded amount");
-
-        // Store history for withdraw unbonded
-        let history = UnbondHistory {
-            batch_id: current_batch.id,
-            time: env.block.time.seconds(),
-            amount: current_batch.requested_with_fee,
-            applied_exchange_rate: state.exchange_rate,
-            withdraw_rate: state.exchange_rate,
-            released: false,
-        };
-        store_unbond_history(deps.storage, current_batch.id, history)?;
-        // batch info must be updated to new batch
-        current_batch.id += 1;
-        current_batch.requested_with_fee = Uint128::zero();
-
-        // state.last_unbonded_time must be updated to the current block time
-        state.last_unbonded_time = env.block.time.seconds();
+        messages.extend(batch_messages);
     }
 
     // Store the new requested_with_fee or id in the current batch
@@ -126,6 +107,20 @@ pub(crate) fn execute_unbond(
         funds: vec![],
     })));
 
+    if !protocol_fee.is_zero() {
+        let collector = config
+            .collector
+            .ok_or_else(|| StdError::generic_err("Protocol fee collector is not set"))?;
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.cluna_contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: collector.to_string(),
+                amount: protocol_fee,
+            })?,
+            funds: vec![],
+        })));
+    }
+
     Ok(Response::new()
         .add_submessages(messages)
         .add_attributes(vec![
@@ -133,15 +128,295 @@ pub(crate) fn execute_unbond(
             attr("from", sender),
             attr("burnt_amount", amount),
             attr("unbonded_amount", amount_with_fee),
-        ]))
+            attr("protocol_fee", protocol_fee),
+            attr("keeper_fee", keeper_fee),
+        ])
+        .add_attributes(slashing_attrs))
+}
+
+/// Undelegate the current batch once the epoch period has passed since the
+/// last one. Permissionless, the sender gets the keeper reward of the batch.
+pub(crate) fn execute_process_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> StdResult<Response> {
+    assert_not_paused(deps.storage, |p| p.unbonding, "unbonding")?;
+    let mut current_batch = CURRENT_BATCH.load(deps.storage)?;
+    let params = PARAMETERS.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    let batch_time = expected_batch_time(&state, &params, 0);
+    if env.block.time.seconds() < batch_time {
+        return Err(StdError::generic_err(format!(
+            "The current batch can not be processed before {}",
+            batch_time
+        )));
+    }
+    if current_batch.requested_with_fee.is_zero() {
+        return Err(StdError::generic_err(
+            "The current batch has no unbond requests",
+        ));
+    }
+
+    // the exchange rate applied to the batch must reflect any slashing
+    let slashing_attrs = slashing(&mut deps, env.clone(), &mut state, &params)?;
+
+    let batch_id = current_batch.id;
+    let keeper_reward = current_batch.keeper_reward;
+    let (messages, undelegated) = process_batch(
+        deps.branch(),
+        &env,
+        &params,
+        &mut state,
+        &mut current_batch,
+        &info.sender,
+    )?;
+
+    CURRENT_BATCH.save(deps.storage, &current_batch)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "process_batch"),
+            attr("batch_id", batch_id.to_string()),
+            attr("undelegated", undelegated),
+            attr("keeper", info.sender),
+            attr("keeper_reward", keeper_reward),
+        ])
+        .add_attributes(slashing_attrs))
+}
+
+/// Undelegates the requests of the current batch at the current exchange
+/// rate, stores its history and starts a new batch. Returns the messages,
+/// including the keeper reward paid to `keeper`, and the undelegated amount.
+/// The caller stores the state and the batch.
+fn process_batch(
+    deps: DepsMut,
+    env: &Env,
+    params: &Parameters,
+    state: &mut State,
+    current_batch: &mut CurrentBatch,
+    keeper: &Addr,
+) -> StdResult<(Vec<SubMsg>, Uint128)> {
+    let mut messages: Vec<SubMsg> = vec![];
+
+    // Apply the current exchange rate.
+    let undelegation_amount = current_batch.requested_with_fee * state.exchange_rate;
+
+    // the contract must stop if
+    if undelegation_amount == Uint128::new(1) {
+        return Err(StdError::generic_err(
+            "Burn amount must be greater than 1 micro unit",
+        ));
+    }
+
+    // Send undelegated requests to possibly more than one validators,
+    // starting from the most over-weight ones
+    let undelegations = pick_unbond_validators(deps.as_ref(), undelegation_amount)?;
+
+    for (validator, amount) in undelegations {
+        sub_delegation(deps.storage, &validator, amount)?;
+        messages.push(SubMsg::new(CosmosMsg::Staking(StakingMsg::Undelegate {
+            validator: validator.to_string(),
+            amount: coin(amount.u128(), &*params.underlying_coin_denom),
+        })));
+    }
+
+    state.total_bond_amount = (state.total_bond_amount.checked_sub(undelegation_amount))
+        .expect("undelegation amount can not be more than stored total bonded amount");
+
+    // Store history for withdraw unbonded
+    let history = UnbondHistory {
+        batch_id: current_batch.id,
+        time: env.block.time.seconds(),
+        amount: current_batch.requested_with_fee,
+        applied_exchange_rate: state.exchange_rate,
+        withdraw_rate: state.exchange_rate,
+        released: false,
+    };
+    store_unbond_history(deps.storage, current_batch.id, history)?;
+
+    if !current_batch.keeper_reward.is_zero() {
+        let config = CONFIG.load(deps.storage)?.assert_initialized()?;
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.cluna_contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: keeper.to_string(),
+                amount: current_batch.keeper_reward,
+            })?,
+            funds: vec![],
+        })));
+    }
+
+    // batch info must be updated to new batch
+    current_batch.id += 1;
+    current_batch.requested_with_fee = Uint128::zero();
+    current_batch.keeper_reward = Uint128::zero();
+
+    // state.last_unbonded_time must be updated to the current block time
+    state.last_unbonded_time = env.block.time.seconds();
+
+    store_exchange_rate_snapshot(deps.storage, env.block.time.seconds(), state)?;
+
+    Ok((messages, undelegation_amount))
+}
+
+/// cLuna amounts of an unbond
+pub(crate) struct UnbondAmounts {
+    pub burnt: Uint128,
+    // added to the unbond requests of the current batch
+    pub requested: Uint128,
+    pub peg_fee: Uint128,
+    // transferred to the collector
+    pub protocol_fee: Uint128,
+    // kept by the vault for the batch processor
+    pub keeper_fee: Uint128,
+}
+
+/// Splits the cLuna sent to unbond into the burnt amount and the fees, given
+/// the state after the slashing check.
+/// Also used by the SimulateUnbond query.
+pub(crate) fn compute_unbond_amounts(
+    state: &State,
+    params: &Parameters,
+    total_supply: Uint128,
+    requested_with_fee: Uint128,
+    amount: Uint128,
+) -> StdResult<UnbondAmounts> {
+    // The unbond fee is sent to the collector and the keeper fee kept for
+    // the batch processor, only the rest is unbonded
+    let protocol_fee = amount * params.unbond_fee;
+    let keeper_fee = amount * params.keeper_fee;
+    let burnt = amount.checked_sub(protocol_fee + keeper_fee)?;
+
+    // Apply peg recovery fee
+    let mut peg_fee = Uint128::zero();
+    if state.exchange_rate < params.er_threshold {
+        let max_peg_fee = burnt * params.peg_recovery_fee;
+        let required_peg_fee =
+            ((total_supply + requested_with_fee).checked_sub(state.total_bond_amount))?;
+        peg_fee = Uint128::min(max_peg_fee, required_peg_fee);
+    }
+
+    Ok(UnbondAmounts {
+        burnt,
+        requested: burnt.checked_sub(peg_fee)?,
+        peg_fee,
+        protocol_fee,
+        keeper_fee,
+    })
+}
+
+/// Time the current unbond batch can be undelegated: once the epoch period
+/// has passed since the last one, by ProcessBatch or the next unbond
+pub(crate) fn expected_batch_time(state: &State, params: &Parameters, now: u64) -> u64 {
+    u64::max(now, state.last_unbonded_time + params.epoch_period + 1)
+}
+
+/// This message must be called by receive_cw20
+/// This message will swap c-luna for luna through the prismswap router, the
+/// luna is sent straight to the sender
+pub(crate) fn execute_instant_unbond(
+    deps: DepsMut,
+    amount: Uint128,
+    sender: String, // human who sent the c-luna to us
+    min_receive: Uint128,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?.assert_initialized()?;
+    assert_not_paused(deps.storage, |p| p.unbonding, "unbonding")?;
+    let params = PARAMETERS.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
+
+    let router = config
+        .prismswap_router
+        .ok_or_else(|| StdError::generic_err("Instant unbond is not enabled"))?;
+    let sender_addr = deps.api.addr_validate(&sender)?;
+
+    let operations = vec![SwapOperation::PrismSwap {
+        offer_asset_info: AssetInfo::Cw20(config.cluna_contract.clone()),
+        ask_asset_info: AssetInfo::Native(params.underlying_coin_denom),
+    }];
+
+    let simulation: SimulateSwapOperationsResponse = deps.querier.query_wasm_smart(
+        router.to_string(),
+        &RouterQueryMsg::SimulateSwapOperations {
+            offer_amount: amount,
+            operations: operations.clone(),
+        },
+    )?;
+    if simulation.amount < min_receive {
+        return Err(StdError::generic_err(format!(
+            "Instant unbond returns {} which is less than min_receive {}",
+            simulation.amount, min_receive
+        )));
+    }
+
+    // Compare with what the standard unbond would return, ignoring the peg
+    // recovery fee and any slashing during the unbonding period
+    let standard_amount = amount * state.exchange_rate;
+    let discount = if standard_amount > simulation.amount {
+        Decimal::from_ratio(standard_amount - simulation.amount, standard_amount)
+    } else {
+        Decimal::zero()
+    };
+
+    // the router asserts min_receive again on the actual swap
+    let swap_msg = WasmMsg::Execute {
+        contract_addr: config.cluna_contract.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: router.to_string(),
+            amount,
+            msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive: Some(min_receive),
+                to: Some(sender_addr),
+            })?,
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new().add_message(swap_msg).add_attributes(vec![
+        attr("action", "instant_unbond"),
+        attr("from", sender),
+        attr("offer_amount", amount),
+        attr("estimated_return", simulation.amount),
+        attr("standard_return", standard_amount),
+        attr("estimated_discount", discount.to_string()),
+    ]))
 }
 
 pub fn execute_withdraw_unbonded(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    owner: Option<String>,
+    recipient: Option<String>,
 ) -> StdResult<Response> {
-    let sender_human = info.sender;
+    assert_not_paused(deps.storage, |p| p.withdrawals, "withdrawals")?;
+    let owner = match owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender.clone(),
+    };
+
+    // operators can only pay the recipient the owner approved
+    let recipient = if owner == info.sender {
+        match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => owner.clone(),
+        }
+    } else {
+        let approval = OPERATORS
+            .may_load(deps.storage, (&owner, &info.sender))?
+            .ok_or_else(|| StdError::generic_err("unauthorized"))?;
+        if recipient.is_some() {
+            return Err(StdError::generic_err(
+                "Operators can not choose the recipient",
+            ));
+        }
+        approval.recipient.unwrap_or_else(|| owner.clone())
+    };
 
     // read params
     let params = PARAMETERS.load(deps.storage)?;
@@ -158,7 +433,7 @@ pub fn execute_withdraw_unbonded(
     // calculate withdraw rate for user requests
     process_withdraw_rate(deps.storage, historical_time, vault_balance)?;
 
-    let withdraw_amount = get_finished_amount(deps.storage, &sender_human, None).unwrap();
+    let withdraw_amount = get_finished_amount(deps.storage, &owner, None).unwrap();
 
     if withdraw_amount.is_zero() {
         return Err(StdError::generic_err(format!(
@@ -168,8 +443,8 @@ pub fn execute_withdraw_unbonded(
     }
 
     // remove the previous batches for the user
-    let deprecated_batches = get_unbond_batches(deps.storage, &sender_human, None)?;
-    remove_unbond_wait_list(deps.storage, deprecated_batches, &sender_human)?;
+    let deprecated_batches = get_unbond_batches(deps.storage, &owner, None)?;
+    remove_unbond_wait_list(deps.storage, deprecated_batches, &owner)?;
 
     // Update previous balance used for calculation in next Luna batch release
     let prev_balance = (vault_balance.checked_sub(withdraw_amount))?;
@@ -180,7 +455,7 @@ pub fn execute_withdraw_unbonded(
 
     // Send the money to the user
     let bank_msg: CosmosMsg = BankMsg::Send {
-        to_address: sender_human.to_string(),
+        to_address: recipient.to_string(),
         amount: coins(withdraw_amount.u128(), &*coin_denom),
     }
     .into();
@@ -190,6 +465,8 @@ pub fn execute_withdraw_unbonded(
             attr("action", "execute_withdraw_unbonded"),
             attr("from", env.contract.address),
             attr("amount", withdraw_amount),
+            attr("owner", owner),
+            attr("recipient", recipient),
         ])
         .add_submessage(SubMsg::new(bank_msg)))
 }
@@ -201,6 +478,23 @@ fn process_withdraw_rate(
     historical_time: u64,
     vault_balance: Uint128,
 ) -> StdResult<()> {
+    let (released, state) = compute_withdraw_rates(storage, historical_time, vault_balance)?;
+    for (batch_id, history) in released {
+        store_unbond_history(storage, batch_id, history)?;
+    }
+    STATE.save(storage, &state)
+}
+
+/// Calculates the withdraw rate of every batch finished since the last
+/// withdrawal, spreading the slashing that happened while unbonding over
+/// them. Returns the released histories and the updated state without
+/// storing them, so simulations can use it as well.
+pub(crate) fn compute_withdraw_rates(
+    storage: &dyn Storage,
+    historical_time: u64,
+    vault_balance: Uint128,
+) -> StdResult<(Vec<(u64, UnbondHistory)>, State)> {
+    let mut released: Vec<(u64, UnbondHistory)> = vec![];
     // balance change of the vault contract must be checked.
     let mut total_unbonded_amount = Uint128::zero();
 
@@ -294,59 +588,199 @@ fn process_withdraw_rate(
             // store the history and mark it as released
             history_for_i.withdraw_rate = new_withdraw_rate;
             history_for_i.released = true;
-            store_unbond_history(storage, iterator, history_for_i)?;
+            released.push((iterator, history_for_i));
             state.last_processed_batch = iterator;
             iterator += 1;
         }
     }
     // Store state.actual_unbonded_amount for future new batches release
     state.actual_unbonded_amount = Uint128::zero();
-    STATE.save(storage, &state)?;
 
-    Ok(())
+    Ok((released, state))
 }
 
-fn pick_unbond_validator(
-    querier: &QuerierWrapper,
-    denom: String,
-    claim: Uint128,
-    delegator: String,
-    block_height: u64,
-) -> StdResult<Vec<SubMsg>> {
-    let mut messages: Vec<SubMsg> = vec![];
-    let mut claimed = claim;
-
-    let all_delegations = querier
-        .query_all_delegations(delegator)
-        .expect("There must be at least one delegation");
-
-    // pick a random validator
-    // if it does not have requested amount, undelegate all it has
-    // and pick another random validator
-    let mut iteration_index = 0;
-    let mut deletable_delegations = all_delegations;
-
-    while claimed.u128() > 0 {
-        let mut rng = XorShiftRng::seed_from_u64(block_height + iteration_index);
-        let random_index = rng.gen_range(0, deletable_delegations.len());
-        let delegation = deletable_delegations.remove(random_index);
-        let val = delegation.amount.amount;
-        let undelegated_amount: Uint128;
-        if val.u128() > claimed.u128() {
-            undelegated_amount = claimed;
-            claimed = Uint128::zero();
-        } else {
-            undelegated_amount = val;
-            claimed = (claimed.checked_sub(val))?;
-        }
-        if undelegated_amount.u128() > 0 {
-            let msgs: CosmosMsg = CosmosMsg::Staking(StakingMsg::Undelegate {
-                validator: delegation.validator,
-                amount: coin(undelegated_amount.u128(), denom.clone()),
-            });
-            messages.push(SubMsg::new(msgs));
-        }
-        iteration_index += 1;
+/// Cancel (part of) an unbond request while its batch has not been
+/// undelegated yet. The waitlist amount is already net of the peg recovery
+/// fee, so that is what gets minted back.
+pub fn execute_cancel_unbond(
+    deps: DepsMut,
+    info: MessageInfo,
+    batch_id: u64,
+    amount: Uint128,
+) -> StdResult<Response> {
+    let config = CONFIG.load(deps.storage)?.assert_initialized()?;
+    assert_not_paused(deps.storage, |p| p.unbonding, "unbonding")?;
+    let mut current_batch = CURRENT_BATCH.load(deps.storage)?;
+
+    if batch_id != current_batch.id {
+        return Err(StdError::generic_err(format!(
+            "Unbond requests of batch {} can not be cancelled anymore",
+            batch_id
+        )));
+    }
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Invalid zero amount"));
+    }
+
+    let requested = UNBOND_WAITLIST
+        .may_load(deps.storage, (&info.sender, batch_id.into()))?
+        .unwrap_or_default();
+    if amount > requested {
+        return Err(StdError::generic_err(format!(
+            "Cancel amount {} is more than the requested amount {}",
+            amount, requested
+        )));
+    }
+
+    // the keeper fee paid for the cancelled share of the requests is refunded
+    let keeper_fees = UNBOND_KEEPER_FEES
+        .may_load(deps.storage, (&info.sender, batch_id.into()))?
+        .unwrap_or_default();
+    let keeper_fee_refund = keeper_fees.multiply_ratio(amount, requested);
+
+    if amount == requested {
+        remove_unbond_wait_list(deps.storage, vec![batch_id], &info.sender)?;
+    } else {
+        UNBOND_WAITLIST.save(
+            deps.storage,
+            (&info.sender, batch_id.into()),
+            &(requested - amount),
+        )?;
+        UNBOND_KEEPER_FEES.save(
+            deps.storage,
+            (&info.sender, batch_id.into()),
+            &(keeper_fees - keeper_fee_refund),
+        )?;
+    }
+
+    current_batch.requested_with_fee = current_batch.requested_with_fee.checked_sub(amount)?;
+    current_batch.keeper_reward = current_batch.keeper_reward.checked_sub(keeper_fee_refund)?;
+    CURRENT_BATCH.save(deps.storage, &current_batch)?;
+
+    // The re-minted amount moves from the requests back to the supply
+    let mut state = STATE.load(deps.storage)?;
+    let total_supply = query_total_issued(deps.as_ref())? + amount;
+    state.update_exchange_rate(total_supply, current_batch.requested_with_fee);
+    STATE.save(deps.storage, &state)?;
+
+    let mut messages = vec![CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.cluna_contract.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: info.sender.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    })];
+    if !keeper_fee_refund.is_zero() {
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.cluna_contract.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: keeper_fee_refund,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "cancel_unbond"),
+        attr("from", info.sender),
+        attr("batch_id", batch_id.to_string()),
+        attr("amount", amount),
+        attr("keeper_fee_refund", keeper_fee_refund),
+    ]))
+}
+
+/// Move the sender's unbond request of a batch to the recipient, merging it
+/// with any request the recipient already has in that batch.
+pub fn execute_transfer_unbond_claim(
+    deps: DepsMut,
+    info: MessageInfo,
+    batch_id: u64,
+    recipient: String,
+) -> StdResult<Response> {
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    if recipient_addr == info.sender {
+        return Err(StdError::generic_err(
+            "Can not transfer a claim to yourself",
+        ));
     }
-    Ok(messages)
+
+    let amount = UNBOND_WAITLIST
+        .may_load(deps.storage, (&info.sender, batch_id.into()))?
+        .ok_or_else(|| {
+            StdError::generic_err(format!("No unbond claim found for batch {}", batch_id))
+        })?;
+
+    // the keeper fees move with the request, to be refunded if it is cancelled
+    let keeper_fees = UNBOND_KEEPER_FEES
+        .may_load(deps.storage, (&info.sender, batch_id.into()))?
+        .unwrap_or_default();
+
+    remove_unbond_wait_list(deps.storage, vec![batch_id], &info.sender)?;
+    store_unbond_wait_list(deps.storage, batch_id, &recipient_addr, amount)?;
+    store_unbond_keeper_fee(deps.storage, batch_id, &recipient_addr, keeper_fees)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_unbond_claim"),
+        attr("from", info.sender),
+        attr("to", recipient),
+        attr("batch_id", batch_id.to_string()),
+        attr("amount", amount),
+    ]))
+}
+
+/// Allow the operator to withdraw unbonded coins on behalf of the sender.
+/// Approving an operator again replaces its recipient.
+pub fn execute_approve_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+    recipient: Option<String>,
+) -> StdResult<Response> {
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    if operator_addr == info.sender {
+        return Err(StdError::generic_err(
+            "Can not approve yourself as operator",
+        ));
+    }
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(&recipient))
+        .transpose()?;
+
+    OPERATORS.save(
+        deps.storage,
+        (&info.sender, &operator_addr),
+        &OperatorApproval {
+            recipient: recipient.clone(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "approve_operator"),
+        attr("owner", info.sender.clone()),
+        attr("operator", operator),
+        attr("recipient", recipient.unwrap_or(info.sender).to_string()),
+    ]))
+}
+
+pub fn execute_revoke_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> StdResult<Response> {
+    let operator_addr = deps.api.addr_validate(&operator)?;
+    if !OPERATORS.has(deps.storage, (&info.sender, &operator_addr)) {
+        return Err(StdError::generic_err(format!(
+            "{} is not an approved operator",
+            operator
+        )));
+    }
+    OPERATORS.remove(deps.storage, (&info.sender, &operator_addr));
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "revoke_operator"),
+        attr("owner", info.sender),
+        attr("operator", operator),
+    ]))
 }
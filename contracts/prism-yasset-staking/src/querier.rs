@@ -1,5 +1,5 @@
 use cosmwasm_std::{to_binary, Addr, QuerierWrapper, QueryRequest, StdResult, Uint128, WasmQuery};
-use prism_protocol::vault::{QueryMsg as VaultQueryMsg, StateResponse};
+use prism_protocol::vault::{ParametersResponse, QueryMsg as VaultQueryMsg, StateResponse};
 
 pub fn query_vault_bond_amount(querier: &QuerierWrapper, vault: Addr) -> StdResult<Uint128> {
     let res: StateResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
@@ -9,3 +9,13 @@ pub fn query_vault_bond_amount(querier: &QuerierWrapper, vault: Addr) -> StdResu
 
     Ok(res.total_bond_amount)
 }
+
+/// Returns the native denom the vault bonds, which rewards are swapped to
+pub fn query_vault_underlying_denom(querier: &QuerierWrapper, vault: &Addr) -> StdResult<String> {
+    let res: ParametersResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: vault.to_string(),
+        msg: to_binary(&VaultQueryMsg::Parameters {})?,
+    }))?;
+
+    Ok(res.underlying_coin_denom)
+}
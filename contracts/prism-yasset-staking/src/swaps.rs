@@ -1,3 +1,4 @@
+use crate::querier::query_vault_underlying_denom;
 use crate::state::CONFIG;
 use cosmwasm_std::{
     attr, to_binary, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
@@ -9,10 +10,8 @@ use prism_protocol::yasset_staking::ExecuteMsg;
 use prismswap::querier::{query_balance, query_token_balance};
 use terra_cosmwasm::{create_swap_msg, ExchangeRatesResponse, TerraMsgWrapper, TerraQuerier};
 
-pub const REWARD_DENOM: &str = "uluna";
-
-/// 1. Swap all native tokens to uluna
-/// 2. Use the uluna to mint pluna and yluna
+/// 1. Swap all native tokens to the vault's underlying denom (e.g. uluna)
+/// 2. Use the underlying to mint pluna and yluna
 /// 3. Deposit pluna and yluna as reward to stakers
 ///
 /// This method should be called after native delegator rewards have already
@@ -22,11 +21,13 @@ pub fn process_delegator_rewards(
     env: Env,
     _info: MessageInfo,
 ) -> StdResult<Response<TerraMsgWrapper>> {
+    let cfg = CONFIG.load(deps.storage)?;
+
     // Find all native denoms for which we have a balance.
     let balances = deps.querier.query_all_balances(&env.contract.address)?;
     let denoms: Vec<String> = balances.iter().map(|item| item.denom.clone()).collect();
 
-    let reward_denom = String::from(REWARD_DENOM);
+    let reward_denom = query_vault_underlying_denom(&deps.querier, &cfg.vault)?;
     let exchange_rates = query_exchange_rates(&deps, reward_denom.clone(), denoms)?;
 
     let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = Vec::new();
@@ -37,7 +38,7 @@ pub fn process_delegator_rewards(
                 .iter()
                 .any(|x| x.quote_denom == coin.denom)
         {
-            // ignore luna and any other denom that's not convertible to luna.
+            // ignore the underlying and any other denom that's not convertible to it.
             continue;
         }
 
@@ -58,7 +59,7 @@ pub fn process_delegator_rewards(
 
 pub fn luna_to_pyluna_hook(deps: DepsMut, env: Env) -> StdResult<Response<TerraMsgWrapper>> {
     let cfg = CONFIG.load(deps.storage)?;
-    let reward_denom = String::from(REWARD_DENOM);
+    let reward_denom = query_vault_underlying_denom(&deps.querier, &cfg.vault)?;
 
     let luna_amt = query_balance(&deps.querier, &env.contract.address, reward_denom.clone())?;
 
@@ -296,6 +296,54 @@ fn test_luna_to_cluna_hook() {
     )
 }
 
+#[test]
+fn test_rewards_follow_vault_denom() {
+    let mut deps = mock_dependencies(&[
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::new(1000u128),
+        },
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::new(150u128),
+        },
+    ]);
+    deps.querier.with_vault_underlying_denom("uatom");
+    init(&mut deps);
+
+    // rewards are swapped to the vault's underlying denom
+    let info = mock_info("vault0000", &[]);
+    let msg = ExecuteMsg::ProcessDelegatorRewards {};
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(create_swap_msg(
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(1000u128),
+            },
+            "uatom".to_string()
+        ))
+    );
+    assert_eq!(res.messages.len(), 2);
+
+    // and bonded in that denom
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let msg = ExecuteMsg::LunaToPylunaHook {};
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "vault0000".to_string(),
+            msg: to_binary(&VaultExecuteMsg::BondSplit { validator: None }).unwrap(),
+            funds: vec![Coin {
+                denom: "uatom".to_string(),
+                amount: Uint128::from(150u128),
+            }],
+        }))
+    );
+}
+
 #[test]
 fn test_deposit_minted_pyluna_hook() {
     let mut deps = mock_dependencies(&[]);
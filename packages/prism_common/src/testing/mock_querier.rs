@@ -13,10 +13,13 @@ use astroport::asset::{AssetInfo as AstroAssetInfo, PairInfo as AstroPairInfo};
 use astroport::factory::PairType;
 use cw20::BalanceResponse as Cw20BalanceResponse;
 use cw_asset::{Asset, AssetInfo};
-use prism_protocol::vault::StateResponse as VaultStateResponse;
+use prism_protocol::vault::{
+    ParametersResponse as VaultParametersResponse, StateResponse as VaultStateResponse,
+};
 use prism_protocol::yasset_staking::RewardAssetWhitelistResponse;
 use prismswap::asset::{PairInfo, PrismSwapAssetInfo};
 use prismswap::pair::{ReverseSimulationResponse, SimulationResponse};
+use prismswap::router::{SimulateSwapOperationsResponse, SwapOperation};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use terra_cosmwasm::{
@@ -93,14 +96,29 @@ impl Querier for WasmMockQuerier {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    Pair { asset_infos: [AssetInfo; 2] },
-    Balance { address: String },
+    Pair {
+        asset_infos: [AssetInfo; 2],
+    },
+    Balance {
+        address: String,
+    },
     TokenInfo {},
     State {},
+    Parameters {},
     RewardAssetWhitelist {},
-    Simulation { offer_asset: Asset },
-    ReverseSimulation { ask_asset: Asset },
-    GetBoost { user: Addr },
+    Simulation {
+        offer_asset: Asset,
+    },
+    ReverseSimulation {
+        ask_asset: Asset,
+    },
+    GetBoost {
+        user: Addr,
+    },
+    SimulateSwapOperations {
+        offer_amount: Uint128,
+        operations: Vec<SwapOperation>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -234,6 +252,25 @@ impl WasmMockQuerier {
                             })
                             .unwrap(),
                         )),
+                        QueryMsg::Parameters {} => SystemResult::Ok(ContractResult::Ok(
+                            to_binary(&VaultParametersResponse {
+                                epoch_period: 30,
+                                underlying_coin_denom: self
+                                    .vault_state_querier
+                                    .underlying_coin_denom
+                                    .clone()
+                                    .unwrap_or_else(|| "uluna".to_string()),
+                                unbonding_period: 2,
+                                peg_recovery_fee: Decimal::zero(),
+                                er_threshold: Decimal::one(),
+                                rebalance_cap: Decimal::zero(),
+                                commission_ceiling: Decimal::one(),
+                                mint_fee: Decimal::zero(),
+                                unbond_fee: Decimal::zero(),
+                                keeper_fee: Decimal::zero(),
+                            })
+                            .unwrap(),
+                        )),
                         QueryMsg::RewardAssetWhitelist {} => SystemResult::Ok(ContractResult::Ok(
                             to_binary(&RewardAssetWhitelistResponse {
                                 assets: vec![
@@ -260,6 +297,28 @@ impl WasmMockQuerier {
                                 .unwrap();
                             SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
                         }
+                        QueryMsg::SimulateSwapOperations { operations, .. } => {
+                            // router simulations are keyed by the first offer asset
+                            let offer_asset_info = match &operations[0] {
+                                SwapOperation::NativeSwap { offer_denom, .. } => {
+                                    AssetInfo::Native(offer_denom.clone())
+                                }
+                                SwapOperation::PrismSwap {
+                                    offer_asset_info, ..
+                                } => offer_asset_info.clone(),
+                            };
+                            let res = self
+                                .simulation_querier
+                                .sim_responses
+                                .get(&(contract_addr.to_string(), offer_asset_info.to_string()))
+                                .unwrap();
+                            SystemResult::Ok(ContractResult::Ok(
+                                to_binary(&SimulateSwapOperationsResponse {
+                                    amount: res.return_amount,
+                                })
+                                .unwrap(),
+                            ))
+                        }
                         QueryMsg::GetBoost { user } => {
                             SystemResult::Ok(self.boost_querier.get_boost(&user).map_or_else(
                                 ContractResult::Err,
@@ -352,12 +411,15 @@ pub(crate) fn pairs_to_map(pairs: &[[AssetInfo; 2]]) -> HashMap<String, [AssetIn
 #[derive(Clone, Default)]
 pub struct VaultStateQuerier {
     total_bond_amount: Uint128,
+    // uluna if not set
+    underlying_coin_denom: Option<String>,
 }
 
 impl VaultStateQuerier {
     pub fn new(total_bond_amount: &Uint128) -> Self {
         VaultStateQuerier {
             total_bond_amount: *total_bond_amount,
+            underlying_coin_denom: None,
         }
     }
 }
@@ -444,7 +506,11 @@ impl WasmMockQuerier {
     }
 
     pub fn with_vault_state(&mut self, total_bond_amount: &Uint128) {
-        self.vault_state_querier = VaultStateQuerier::new(total_bond_amount);
+        self.vault_state_querier.total_bond_amount = *total_bond_amount;
+    }
+
+    pub fn with_vault_underlying_denom(&mut self, denom: &str) {
+        self.vault_state_querier.underlying_coin_denom = Some(denom.to_string());
     }
 
     pub fn with_prismswap_sim_response(
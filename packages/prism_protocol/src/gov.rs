@@ -1,4 +1,4 @@
-use cosmwasm_std::{Binary, Decimal, Uint128};
+use cosmwasm_std::{CosmosMsg, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -18,6 +18,10 @@ pub struct InstantiateMsg {
     pub redemption_time: u64,
     pub poll_gas_limit: u64,
     pub token_code_id: u64,
+    /// Fee of instant xPRISM redemptions, which are disabled if not set
+    pub instant_redemption_fee: Option<Decimal>,
+    /// Address allowed to veto passed polls during the effective delay
+    pub guardian: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -34,7 +38,11 @@ pub enum ExecuteMsg {
         snapshot_period: Option<u64>,
         redemption_time: Option<u64>,
         poll_gas_limit: Option<u64>,
+        instant_redemption_fee: Option<Decimal>,
+        guardian: Option<String>,
     },
+    /// Unset the guardian, deposits it still holds are refunded on execution
+    RemoveGuardian {},
     CastVote {
         poll_id: u64,
         vote: VoteOption,
@@ -43,16 +51,46 @@ pub enum ExecuteMsg {
     WithdrawVotingTokens {
         amount: Option<Uint128>,
     },
+    /// Lock staked xPRISM for `duration` seconds in exchange for a voting power
+    /// multiplier that decays linearly until the lock expires
+    LockVotingTokens {
+        amount: Uint128,
+        duration: u64,
+    },
     EndPoll {
         poll_id: u64,
     },
     ExecutePoll {
         poll_id: u64,
     },
+    /// Dispatches the messages of a passed poll, only callable by the contract
+    /// itself so that they all succeed or fail together
+    ExecutePollMsgs {
+        poll_id: u64,
+    },
     SnapshotPoll {
         poll_id: u64,
     },
+    /// Cancel a poll in progress, by its creator before any vote is cast or by
+    /// the owner at any time
+    CancelPoll {
+        poll_id: u64,
+    },
+    /// Veto a passed poll before its effective delay expires, only callable
+    /// by the guardian
+    VetoPoll {
+        poll_id: u64,
+    },
     ClaimRedeemedXprism {},
+    /// Count the sender's staked xPRISM towards the votes of `delegate`,
+    /// on polls the sender does not vote on directly. A vote cast by the
+    /// delegate locks the sender's stake like a direct vote, until the poll
+    /// ends or is cancelled
+    DelegateVotes {
+        delegate: String,
+    },
+    /// Stop delegating, votes the delegate already cast keep counting
+    UndelegateVotes {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -65,19 +103,28 @@ pub enum Cw20HookMsg {
         title: String,
         description: String,
         link: Option<String>,
-        execute_msg: Option<PollExecuteMsg>,
+        /// Messages dispatched in order when the poll is executed. Supported are
+        /// wasm execute, migrate, update admin, clear admin and bank send
+        execute_msgs: Option<Vec<CosmosMsg>>,
+        /// Defaults to a yes/no poll
+        poll_type: Option<PollType>,
+        /// Choices of single choice and approval polls, which carry their own
+        /// execute messages instead of `execute_msgs`
+        choices: Option<Vec<PollChoiceMsg>>,
     },
     MintXprism {
         receiver: Option<String>,
     },
     RedeemXprism {},
+    /// Redeem PRISM without waiting for the redemption time, minus the instant
+    /// redemption fee which stays in the pool
+    InstantRedeemXprism {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub struct PollExecuteMsg {
-    pub contract: String,
-    pub msg: Binary,
+pub struct PollChoiceMsg {
+    pub description: String,
+    pub execute_msgs: Option<Vec<CosmosMsg>>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -113,6 +160,30 @@ pub enum QueryMsg {
         order_by: Option<OrderBy>,
     },
     XprismState {},
+    /// Passed polls waiting for execution, with their earliest execution time
+    QueuedPolls {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    LockPositions {
+        address: String,
+    },
+    VotingPower {
+        address: String,
+    },
+    VoteDelegation {
+        address: String,
+    },
+    DelegatedPower {
+        delegate: String,
+    },
+    Delegators {
+        delegate: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -128,6 +199,8 @@ pub struct ConfigResponse {
     pub snapshot_period: u64,
     pub redemption_time: u64,
     pub poll_gas_limit: u64,
+    pub instant_redemption_fee: Option<Decimal>,
+    pub guardian: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -140,13 +213,23 @@ pub struct PollResponse {
     pub description: String,
     pub link: Option<String>,
     pub deposit_amount: Uint128,
-    pub execute_data: Option<PollExecuteMsg>,
+    pub execute_msgs: Vec<CosmosMsg>,
     pub yes_votes: Uint128,     // balance
     pub no_votes: Uint128,      // balance
     pub abstain_votes: Uint128, // balance
     pub supply_snapshot: Option<Uint128>,
     pub required_quorum: Decimal,
     pub required_threshold: Decimal,
+    pub poll_type: PollType,
+    pub choices: Vec<PollChoiceResponse>,
+    pub winning_choice: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollChoiceResponse {
+    pub description: String,
+    pub execute_msgs: Vec<CosmosMsg>,
+    pub votes: Uint128, // balance
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -154,12 +237,47 @@ pub struct PollsResponse {
     pub polls: Vec<PollResponse>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct QueuedPollResponse {
+    pub poll_id: u64,
+    pub title: String,
+    pub end_time: u64,
+    /// end_time plus the current effective delay
+    pub earliest_execution_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct QueuedPollsResponse {
+    pub polls: Vec<QueuedPollResponse>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct VotingTokensResponse {
     pub balance: Uint128,
     pub locked_balance: Vec<(u64, VoterInfo)>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct LockPositionResponse {
+    pub amount: Uint128,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Current voting power multiplier of the locked amount
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct LockPositionsResponse {
+    pub locks: Vec<LockPositionResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    pub balance: Uint128,
+    pub locked_amount: Uint128,
+    pub voting_power: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct VotersResponseItem {
     pub voter: String,
@@ -178,6 +296,30 @@ pub struct VoterInfo {
     pub balance: Uint128,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteDelegationResponse {
+    pub delegate: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegatedPowerResponse {
+    pub delegate: String,
+    /// staked xPRISM of all delegators
+    pub delegated_power: Uint128,
+    pub delegators: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegatorsResponseItem {
+    pub delegator: String,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegatorsResponse {
+    pub delegators: Vec<DelegatorsResponseItem>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct PrismWithdrawOrdersResponse {
     pub claimable_amount: Uint128,
@@ -191,6 +333,7 @@ pub struct XprismStateResponse {
     pub effective_underlying_prism: Uint128,
     pub total_pending_withdraw_xprism: Uint128,
     pub total_pending_withdraw_prism: Uint128,
+    pub instant_redemption_fee: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -201,6 +344,8 @@ pub enum PollStatus {
     Rejected,
     Executed,
     Failed,
+    Cancelled,
+    Vetoed,
 }
 
 impl fmt::Display for PollStatus {
@@ -209,20 +354,50 @@ impl fmt::Display for PollStatus {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollType {
+    /// Yes, no or abstain on the execute messages of the poll
+    YesNo,
+    /// Voters pick one of the choices
+    SingleChoice,
+    /// Voters approve any number of the choices, each with their full amount
+    Approval,
+}
+
+// derived enum defaults need a newer compiler than the optimizer image ships
+#[allow(clippy::derivable_impls)]
+impl Default for PollType {
+    fn default() -> Self {
+        PollType::YesNo
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum VoteOption {
     Yes,
     No,
     Abstain,
+    /// Indexes of the voted choices of a single choice or approval poll
+    Choices(Vec<u32>),
 }
 
 impl fmt::Display for VoteOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
+        match self {
             VoteOption::Yes => write!(f, "yes"),
             VoteOption::No => write!(f, "no"),
             VoteOption::Abstain => write!(f, "abstain"),
+            VoteOption::Choices(choices) => write!(
+                f,
+                "choices:{}",
+                choices
+                    .iter()
+                    .map(|choice| choice.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
         }
     }
 }
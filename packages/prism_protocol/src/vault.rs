@@ -3,6 +3,9 @@ use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// Upper bound of the mint, unbond and keeper fees
+pub const MAX_PROTOCOL_FEE: &str = "0.05";
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub epoch_period: u64,
@@ -14,6 +17,12 @@ pub struct InstantiateMsg {
     pub token_admin: String,
     pub token_code_id: u64,
     pub manager: String,
+    /// Symbol of the underlying asset used to name the c/p/y tokens,
+    /// "Luna" if not given
+    pub underlying_symbol: Option<String>,
+    /// Share of the total delegation Rebalance can move per epoch, 10% if
+    /// not given
+    pub rebalance_cap: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -29,8 +38,20 @@ pub enum ExecuteMsg {
         yluna_staking: Option<String>,
         airdrop_registry_contract: Option<String>,
         manager: Option<String>,
+        prismswap_router: Option<String>,
+        guardian: Option<String>,
+        collector: Option<String>,
     },
 
+    /// Unset the prismswap router, disabling InstantUnbond
+    RemovePrismswapRouter {},
+
+    /// Unset the guardian, leaving pausing to the owner
+    RemoveGuardian {},
+
+    /// Unset the protocol fee collector, only once the protocol fees are zero
+    RemoveCollector {},
+
     /// Register receives the reward contract address
     RegisterValidator { validator: String },
 
@@ -46,6 +67,27 @@ pub enum ExecuteMsg {
         unbonding_period: Option<u64>,
         peg_recovery_fee: Option<Decimal>,
         er_threshold: Option<Decimal>,
+        rebalance_cap: Option<Decimal>,
+        commission_ceiling: Option<Decimal>,
+        mint_fee: Option<Decimal>,
+        unbond_fee: Option<Decimal>,
+        keeper_fee: Option<Decimal>,
+    },
+
+    /// Set the target delegation weights of whitelisted validators
+    UpdateValidatorWeights { weights: Vec<ValidatorWeight> },
+
+    ////////////////////
+    /// Guardian's operations
+    ////////////////////
+
+    /// Pause or unpause categories of operations, fields left out are kept.
+    /// Also allowed for the owner
+    UpdatePauseStatus {
+        minting: Option<bool>,
+        refraction: Option<bool>,
+        unbonding: Option<bool>,
+        withdrawals: Option<bool>,
     },
 
     ////////////////////
@@ -66,7 +108,8 @@ pub enum ExecuteMsg {
     /// Receives `amount` in underlying coin denom from sender.
     /// Delegate `amount` to a specific `validator`.
     /// Issue `amount` / exchange_rate for the user.
-    /// If validator not present, pick a pseudo-randomly generated validator
+    /// If validator not present, split `amount` across the whitelisted
+    /// validators towards their target weights
     Bond { validator: Option<String> },
 
     /// do bond, then split cluna into yluna and pluna
@@ -75,12 +118,45 @@ pub enum ExecuteMsg {
     /// Update global index
     UpdateGlobalIndex { airdrop_hooks: Option<Vec<Binary>> },
 
-    /// Send back unbonded coin to the user
-    WithdrawUnbonded {},
+    /// Send back unbonded coin to the user, or to the given recipient.
+    /// An approved operator can withdraw on behalf of `owner`, the coins then
+    /// go to the recipient named in the approval.
+    WithdrawUnbonded {
+        owner: Option<String>,
+        recipient: Option<String>,
+    },
+
+    /// Allow the operator to withdraw unbonded coins on behalf of the sender,
+    /// paying them to the recipient (the sender by default)
+    ApproveOperator {
+        operator: String,
+        recipient: Option<String>,
+    },
+
+    /// Remove an operator approval
+    RevokeOperator { operator: String },
+
+    /// Cancel an unbond request of the current batch, before it is undelegated
+    CancelUnbond { batch_id: u64, amount: Uint128 },
+
+    /// Transfer the sender's unbond request of a batch to the recipient
+    TransferUnbondClaim { batch_id: u64, recipient: String },
+
+    /// Undelegate the current unbond batch once the epoch period has passed
+    /// since the last one, paying the batch's keeper reward to the sender
+    ProcessBatch {},
 
     /// Check whether the slashing has happened or not
     CheckSlashing {},
 
+    /// Deregister whitelisted validators that left the active set (e.g. jailed)
+    /// or charge more than `commission_ceiling`, and move their stake away
+    CheckValidators {},
+
+    /// Redelegate from over-weight to under-weight validators, moving at most
+    /// `rebalance_cap` of the total delegation per epoch
+    Rebalance { max_moves: Option<u32> },
+
     ////////////////////
     /// cAsset's operations
     ///////////////////
@@ -93,8 +169,12 @@ pub enum ExecuteMsg {
     /// Split cLuna into yLuna and pLuna
     Split { amount: Uint128 },
 
-    /// Merge yLuna and pLuna into cLuna
-    Merge { amount: Uint128 },
+    /// Merge yLuna and pLuna into cLuna, paid to the recipient (the sender
+    /// by default)
+    Merge {
+        amount: Uint128,
+        recipient: Option<String>,
+    },
 
     ////////////////////
     /// internal operations
@@ -126,13 +206,82 @@ pub enum QueryMsg {
     AllHistory {
         start_from: Option<u64>,
         limit: Option<u32>,
+        /// only list released or unreleased batches
+        released: Option<bool>,
+    },
+    UserUnbondStatus {
+        address: String,
+        start_from: Option<u64>,
+        limit: Option<u32>,
+    },
+    UnbondClaim {
+        address: String,
+        batch_id: u64,
+    },
+    OperatorApproval {
+        owner: String,
+        operator: String,
+    },
+    ValidatorWeights {},
+    ExchangeRateHistory {
+        start_after: Option<u64>,
+        end_before: Option<u64>,
+        limit: Option<u32>,
+    },
+    ValidatorStatuses {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    SlashingEvents {
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
+    Delegations {},
+    PauseStatus {},
+    SimulateBond {
+        amount: Uint128,
+    },
+    SimulateUnbond {
+        amount: Uint128,
+    },
+    SimulateWithdraw {
+        address: String,
+    },
+    NextBatchTime {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     Unbond {},
+    /// Swap cLuna for Luna through the prismswap router instead of waiting
+    /// for the unbonding period, failing if less than `min_receive` is returned
+    InstantUnbond {
+        min_receive: Uint128,
+    },
+    /// Split the sent cLuna into yLuna and pLuna for the recipient (the
+    /// sender by default)
+    Split {
+        recipient: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorWeight {
+    pub validator: String,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeregistrationReason {
+    /// Not part of the active validator set anymore and holding no vault stake
+    Missing {},
+    /// Dropped out of the active validator set while the vault still
+    /// delegates to it, e.g. jailed
+    Jailed {},
+    /// Commission above the vault commission ceiling
+    HighCommission { commission: Decimal },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -145,6 +294,63 @@ pub struct ConfigResponse {
     pub airdrop_registry_contract: String,
     pub initialized: bool,
     pub manager: String,
+    pub prismswap_router: Option<String>,
+    pub guardian: Option<String>,
+    pub underlying_symbol: String,
+    pub collector: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ParametersResponse {
+    pub epoch_period: u64,
+    pub underlying_coin_denom: String,
+    pub unbonding_period: u64,
+    pub peg_recovery_fee: Decimal,
+    pub er_threshold: Decimal,
+    pub rebalance_cap: Decimal,
+    pub commission_ceiling: Decimal,
+    pub mint_fee: Decimal,
+    pub unbond_fee: Decimal,
+    pub keeper_fee: Decimal,
+}
+
+/// cLuna amounts of a bond of the underlying
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateBondResponse {
+    pub minted: Uint128,
+    pub peg_recovery_fee: Uint128,
+    pub protocol_fee: Uint128,
+    pub exchange_rate: Decimal,
+}
+
+/// cLuna amounts of an unbond, with the expected underlying received and the
+/// time it can be withdrawn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateUnbondResponse {
+    pub burnt: Uint128,
+    pub peg_recovery_fee: Uint128,
+    pub protocol_fee: Uint128,
+    pub keeper_fee: Uint128,
+    pub received: Uint128,
+    pub release_time: u64,
+}
+
+/// Underlying amounts of the unbond requests of an address
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateWithdrawResponse {
+    /// withdrawn by WithdrawUnbonded now
+    pub received: Uint128,
+    /// still unbonding, estimated at the applied or current exchange rate
+    pub pending: Uint128,
+    pub next_release_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStatusResponse {
+    pub minting: bool,
+    pub refraction: bool,
+    pub unbonding: bool,
+    pub withdrawals: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
@@ -167,6 +373,17 @@ pub struct WhitelistedValidatorsResponse {
 pub struct CurrentBatchResponse {
     pub id: u64,
     pub requested_with_fee: Uint128,
+    pub keeper_reward: Uint128,
+}
+
+/// Earliest time ProcessBatch can undelegate the current batch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NextBatchTimeResponse {
+    pub batch_id: u64,
+    pub time: u64,
+    pub requested_with_fee: Uint128,
+    /// cLuna paid to the sender of ProcessBatch
+    pub keeper_reward: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -180,6 +397,63 @@ pub struct UnbondRequestsResponse {
     pub requests: UnbondRequestResponse,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateSnapshotResponse {
+    pub id: u64,
+    pub time: u64,
+    pub exchange_rate: Decimal,
+    pub total_bond_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateHistoryResponse {
+    pub history: Vec<ExchangeRateSnapshotResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlashingEventResponse {
+    pub id: u64,
+    pub validator: String,
+    pub expected_delegation: Uint128,
+    pub actual_delegation: Uint128,
+    pub time: u64,
+    pub rate_before: Decimal,
+    pub rate_after: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SlashingEventsResponse {
+    pub events: Vec<SlashingEventResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub validator: String,
+    pub expected: Uint128,
+    pub actual: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationsResponse {
+    pub delegations: Vec<DelegationResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OperatorApprovalResponse {
+    pub owner: String,
+    pub operator: String,
+    pub approved: bool,
+    pub recipient: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondClaimResponse {
+    pub address: String,
+    pub batch_id: u64,
+    pub amount: Uint128,
+    pub released: bool,
+}
+
 pub type UnbondRequestResponse = Vec<(u64, Uint128)>;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -187,6 +461,28 @@ pub struct AllHistoryResponse {
     pub history: Vec<UnbondHistoryResponse>,
 }
 
+/// Unbond requests of an address joined with their batch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserUnbondStatusResponse {
+    pub address: String,
+    pub requests: Vec<UnbondRequestStatusResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondRequestStatusResponse {
+    pub batch_id: u64,
+    pub amount: Uint128,
+    /// time the batch was undelegated, None for the current batch
+    pub batch_time: Option<u64>,
+    /// earliest time the request can be withdrawn
+    pub release_time: u64,
+    pub applied_exchange_rate: Option<Decimal>,
+    pub withdraw_rate: Option<Decimal>,
+    pub released: bool,
+    /// whether WithdrawUnbonded pays it out now
+    pub claimable: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct UnbondHistoryResponse {
     pub batch_id: u64,
@@ -196,3 +492,30 @@ pub struct UnbondHistoryResponse {
     pub withdraw_rate: Decimal,
     pub released: bool,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorWeightsResponse {
+    pub total_delegated: Uint128,
+    pub validators: Vec<ValidatorWeightResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorWeightResponse {
+    pub validator: String,
+    pub weight: u64,
+    pub delegated: Uint128,
+    pub target_share: Decimal,
+    pub actual_share: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorStatusesResponse {
+    pub validators: Vec<ValidatorStatusResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValidatorStatusResponse {
+    pub validator: String,
+    pub reason: DeregistrationReason,
+    pub time: u64,
+}